@@ -0,0 +1,233 @@
+pub type Interval = (i64, i64);
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        let (start, end) = interval;
+        if start > end {
+            return;
+        }
+        let lo = self
+            .intervals
+            .partition_point(|&(_, e)| e < start.saturating_sub(1));
+        let hi = self
+            .intervals
+            .partition_point(|&(s, _)| s <= end.saturating_add(1));
+        let merged = if lo < hi {
+            (
+                start.min(self.intervals[lo].0),
+                end.max(self.intervals[hi - 1].1),
+            )
+        } else {
+            interval
+        };
+        self.intervals.splice(lo..hi, [merged]);
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = self.clone();
+        for interval in &other.intervals {
+            result.insert(*interval);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = IntervalSet::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (s1, e1) = self.intervals[i];
+            let (s2, e2) = other.intervals[j];
+            if s1.max(s2) <= e1.min(e2) {
+                result.intervals.push((s1.max(s2), e1.min(e2)));
+            }
+            if e1 < e2 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        result
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = IntervalSet::new();
+        let mut j = 0;
+        for &(s, e) in &self.intervals {
+            while j < other.intervals.len() && other.intervals[j].1 < s {
+                j += 1;
+            }
+            let mut start = Some(s);
+            let mut k = j;
+            while let Some(current) = start {
+                match other.intervals.get(k) {
+                    Some(&(s2, e2)) if s2 <= e => {
+                        if s2 > current {
+                            result.intervals.push((current, s2 - 1));
+                        }
+                        start = if e2 < e {
+                            Some(current.max(e2 + 1))
+                        } else {
+                            None
+                        };
+                        k += 1;
+                    }
+                    _ => {
+                        result.intervals.push((current, e));
+                        start = None;
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self.intervals.partition_point(|&(_, e)| e < value);
+        i < self.intervals.len() && self.intervals[i].0 <= value
+    }
+
+    pub fn contains_interval(&self, interval: Interval) -> bool {
+        let i = self.intervals.partition_point(|&(_, e)| e < interval.0);
+        i < self.intervals.len()
+            && self.intervals[i].0 <= interval.0
+            && interval.1 <= self.intervals[i].1
+    }
+
+    pub fn is_superset(&self, other: &IntervalSet) -> bool {
+        other.intervals.iter().all(|i| self.contains_interval(*i))
+    }
+
+    pub fn coverage(&self) -> u64 {
+        self.intervals.iter().map(|(s, e)| s.abs_diff(*e) + 1).sum()
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> Self {
+        let mut set = IntervalSet::new();
+        for interval in iter {
+            set.insert(interval);
+        }
+        set
+    }
+}
+
+pub fn covered_at_least(intervals: &[Interval], k: usize) -> IntervalSet {
+    let mut events: Vec<(i64, i64)> = vec![];
+    for &(s, e) in intervals.iter().filter(|(s, e)| s <= e) {
+        events.push((s, 1));
+        events.push((e + 1, -1));
+    }
+    events.sort();
+    let mut result = IntervalSet::new();
+    let mut count = 0;
+    let mut start = None;
+    for (position, delta) in events {
+        count += delta;
+        match start {
+            None if count >= k as i64 => start = Some(position),
+            Some(s) if count < k as i64 => {
+                result.insert((s, position - 1));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{covered_at_least, Interval, IntervalSet};
+
+    fn merge(ranges: Vec<Interval>, range: Interval) -> Vec<Interval> {
+        let mut set: IntervalSet = ranges.into_iter().collect();
+        set.insert(range);
+        set.intervals().to_vec()
+    }
+
+    #[test]
+    fn test_insert() {
+        // Add
+        assert_eq!(vec![(2, 3)], merge(vec![], (2, 3)));
+
+        // Span
+        assert_eq!(vec![(0, 5)], merge(vec![(0, 5)], (3, 4)));
+        assert_eq!(vec![(0, 5)], merge(vec![(3, 4)], (0, 5)));
+        assert_eq!(vec![(1, 4)], merge(vec![(1, 4)], (3, 3)));
+        assert_eq!(vec![(1, 4)], merge(vec![(3, 3)], (1, 4)));
+
+        // Disjoint
+        assert_eq!(vec![(0, 1), (3, 4)], merge(vec![(3, 4)], (0, 1)));
+        assert_eq!(vec![(0, 1), (3, 4)], merge(vec![(0, 1)], (3, 4)));
+
+        // Meet
+        assert_eq!(vec![(0, 3)], merge(vec![(2, 3)], (0, 1)));
+        assert_eq!(vec![(0, 3)], merge(vec![(0, 1)], (2, 3)));
+        assert_eq!(vec![(1, 3)], merge(vec![(2, 3)], (1, 1)));
+        assert_eq!(vec![(1, 3)], merge(vec![(1, 1)], (2, 3)));
+        assert_eq!(vec![(1, 3)], merge(vec![(3, 3)], (1, 2)));
+        assert_eq!(vec![(1, 3)], merge(vec![(1, 2)], (3, 3)));
+
+        // Straddle
+        assert_eq!(vec![(0, 20)], merge(vec![(0, 10)], (8, 20)));
+        assert_eq!(vec![(0, 20)], merge(vec![(8, 20)], (0, 10)));
+        assert_eq!(vec![(0, 20)], merge(vec![(0, 2), (5, 6), (9, 12)], (1, 20)));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: IntervalSet = vec![(0, 10), (20, 30)].into_iter().collect();
+        let b: IntervalSet = vec![(5, 25), (28, 40)].into_iter().collect();
+        assert_eq!(vec![(0, 40)], a.union(&b).intervals());
+        assert_eq!(
+            vec![(5, 10), (20, 25), (28, 30)],
+            a.intersection(&b).intervals()
+        );
+        assert_eq!(vec![(0, 4), (26, 27)], a.difference(&b).intervals());
+        assert_eq!(vec![(11, 19), (31, 40)], b.difference(&a).intervals());
+        assert!(a.difference(&a).is_empty());
+        assert_eq!(22, a.coverage());
+    }
+
+    #[test]
+    fn test_containment() {
+        let a: IntervalSet = vec![(0, 10), (20, 30)].into_iter().collect();
+        assert!(a.contains(0));
+        assert!(a.contains(25));
+        assert!(!a.contains(15));
+        assert!(a.contains_interval((2, 8)));
+        assert!(!a.contains_interval((8, 22)));
+        assert!(a.is_superset(&vec![(1, 2), (29, 30)].into_iter().collect()));
+        assert!(!a.is_superset(&vec![(9, 11)].into_iter().collect()));
+    }
+
+    #[test]
+    fn test_covered_at_least() {
+        let intervals = vec![(0, 4), (2, 6), (3, 3), (10, 12)];
+        assert_eq!(
+            vec![(0, 6), (10, 12)],
+            covered_at_least(&intervals, 1).intervals()
+        );
+        assert_eq!(vec![(2, 4)], covered_at_least(&intervals, 2).intervals());
+        assert_eq!(vec![(3, 3)], covered_at_least(&intervals, 3).intervals());
+        assert!(covered_at_least(&intervals, 4).is_empty());
+    }
+}
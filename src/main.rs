@@ -1,3 +1,4 @@
+mod interval;
 mod parser;
 mod q01;
 mod q02;
@@ -24,6 +25,63 @@ mod q22;
 mod q23;
 mod q24;
 mod q25;
+mod watch;
+
+use std::env;
+use std::io;
+use watch::Solver;
+
+macro_rules! solvers {
+    ($q:ident) => {
+        vec![
+            ("Part 1", (|| Ok($q::part_1()?.to_string())) as Solver),
+            ("Part 2", || Ok($q::part_2()?.to_string())),
+            ("Part 1 (sample)", || Ok($q::_part_1_sample()?.to_string())),
+            ("Part 2 (sample)", || Ok($q::_part_2_sample()?.to_string())),
+        ]
+    };
+}
+
+fn solvers(number: u8) -> Vec<(&'static str, Solver)> {
+    match number {
+        1 => solvers!(q01),
+        2 => solvers!(q02),
+        3 => solvers!(q03),
+        4 => solvers!(q04),
+        5 => solvers!(q05),
+        6 => solvers!(q06),
+        7 => solvers!(q07),
+        8 => solvers!(q08),
+        9 => {
+            let mut s = solvers!(q09);
+            s.push(("Part 2 (sample 2)", || {
+                Ok(q09::_part_2_sample_2()?.to_string())
+            }));
+            s
+        }
+        10 => solvers!(q10),
+        11 => solvers!(q11),
+        12 => solvers!(q12),
+        13 => solvers!(q13),
+        14 => solvers!(q14),
+        15 => solvers!(q15),
+        16 => solvers!(q16),
+        17 => solvers!(q17),
+        18 => solvers!(q18),
+        19 => solvers!(q19),
+        20 => solvers!(q20),
+        21 => solvers!(q21),
+        22 => solvers!(q22),
+        23 => solvers!(q23),
+        24 => solvers!(q24),
+        25 => vec![
+            ("Part 1", q25::part_1 as Solver),
+            ("Part 2", q25::part_2),
+            ("Part 1 (sample)", q25::_part_1_sample),
+        ],
+        _ => vec![],
+    }
+}
 
 fn print_result(number: u8, part1: &impl ToString, part2: &impl ToString) {
     println!("Question {:}", number);
@@ -35,32 +93,48 @@ fn print_result(number: u8, part1: &impl ToString, part2: &impl ToString) {
     println!("------------");
 }
 
-fn main() -> std::io::Result<()> {
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.iter().map(|a| a.as_str()).collect::<Vec<_>>()[..] {
+        [] => {}
+        ["watch", day] => {
+            let number = parse_day(day)?;
+            return watch::watch(number, &solvers(number));
+        }
+        [day, ref tool @ ..] if !tool.is_empty() => return run_tool(parse_day(day)?, tool),
+        _ => return parser::error("Usage: advent_of_code_2022 [watch <day> | <day> <tool> ...]"),
+    }
     println!("------------");
-    print_result(1, &q01::part_1()?, &q01::part_2()?);
-    print_result(2, &q02::part_1()?, &q02::part_2()?);
-    print_result(3, &q03::part_1()?, &q03::part_2()?);
-    print_result(4, &q04::part_1()?, &q04::part_2()?);
-    print_result(5, &q05::part_1()?, &q05::part_2()?);
-    print_result(6, &q06::part_1()?, &q06::part_2()?);
-    print_result(7, &q07::part_1()?, &q07::part_2()?);
-    print_result(8, &q08::part_1()?, &q08::part_2()?);
-    print_result(9, &q09::part_1()?, &q09::part_2()?);
-    print_result(10, &q10::part_1()?, &q10::part_2()?);
-    print_result(11, &q11::part_1()?, &q11::part_2()?);
-    print_result(12, &q12::part_1()?, &q12::part_2()?);
-    print_result(13, &q13::part_1()?, &q13::part_2()?);
-    print_result(14, &q14::part_1()?, &q14::part_2()?);
-    print_result(15, &q15::part_1()?, &q15::part_2()?);
-    print_result(16, &q16::part_1()?, &q16::part_2()?);
-    print_result(17, &q17::part_1()?, &q17::part_2()?);
-    print_result(18, &q18::part_1()?, &q18::part_2()?);
-    print_result(19, &q19::part_1()?, &q19::part_2()?);
-    print_result(20, &q20::part_1()?, &q20::part_2()?);
-    print_result(21, &q21::part_1()?, &q21::part_2()?);
-    print_result(22, &q22::part_1()?, &q22::part_2()?);
-    print_result(23, &q23::part_1()?, &q23::part_2()?);
-    print_result(24, &q24::part_1()?, &q24::part_2()?);
-    print_result(25, &q25::part_1()?, &q25::part_2()?);
+    for number in 1..=25 {
+        let solvers = solvers(number);
+        print_result(number, &(solvers[0].1)()?, &(solvers[1].1)()?);
+    }
     Ok(())
 }
+
+fn run_tool(number: u8, args: &[&str]) -> io::Result<()> {
+    match number {
+        1 => q01::tool(args),
+        2 => q02::tool(args),
+        3 => q03::tool(args),
+        4 => q04::tool(args),
+        5 => q05::tool(args),
+        6 => q06::tool(args),
+        7 => q07::tool(args),
+        8 => q08::tool(args),
+        9 => q09::tool(args),
+        10 => q10::tool(args),
+        11 => q11::tool(args),
+        12 => q12::tool(args),
+        13 => q13::tool(args),
+        14 => q14::tool(args),
+        _ => parser::error(&format!("Question {} has no tools", number)),
+    }
+}
+
+fn parse_day(day: &str) -> io::Result<u8> {
+    match day.parse() {
+        Ok(number) if (1..=25).contains(&number) => Ok(number),
+        _ => parser::error("Day must be a number from 1 to 25"),
+    }
+}
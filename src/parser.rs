@@ -1,17 +1,25 @@
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
+use std::str::FromStr;
 
 pub fn error<T>(message: &str) -> io::Result<T> {
     Err(io::Error::new(io::ErrorKind::Other, message))
 }
 
-pub fn parse_lines<'a, T>(input: &str) -> io::Result<FileInput<FileLines, T>> {
-    FileInput::new(FileLines::new(input)?)
+pub fn arg<T: FromStr>(value: &str) -> io::Result<T> {
+    match value.parse() {
+        Ok(v) => Ok(v),
+        Err(_) => error(&format!("Invalid argument: {}", value)),
+    }
 }
 
-pub fn parse_bytes<'a, T>(input: &str, split: &u8) -> io::Result<FileInput<FileSplit, T>> {
-    FileInput::new(FileSplit::new(input, split)?)
+pub fn file_arg<'a>(args: &[&'a str], default: &'a str) -> &'a str {
+    args.first().copied().unwrap_or(default)
+}
+
+pub fn parse_lines<'a, T>(input: &str) -> io::Result<FileInput<FileLines, T>> {
+    FileInput::new(FileLines::new(input)?)
 }
 
 pub fn parse_from<'a, T, U>(source: &'a mut T) -> Option<U>
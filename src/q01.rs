@@ -1,51 +1,272 @@
-use crate::parser::FileLines;
+use crate::parser::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io;
+use std::io::Write;
 
 const INPUT_FILE: &str = "input/input01.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input01_sample.txt";
 
+#[derive(Debug, PartialEq)]
+struct Elf {
+    index: usize,
+    items: Vec<u32>,
+}
+
+impl Elf {
+    fn total(&self) -> u32 {
+        self.items.iter().sum()
+    }
+}
+
+#[derive(Debug)]
+struct Inventory {
+    elves: Vec<Elf>,
+}
+
+impl Inventory {
+    fn top(&self, n: usize) -> Vec<&Elf> {
+        let mut elves: Vec<&Elf> = self.elves.iter().collect();
+        elves.sort_by_key(|elf| Reverse(elf.total()));
+        elves.truncate(n);
+        elves
+    }
+
+    fn top_total(&self, n: usize) -> u32 {
+        self.top(n).iter().map(|elf| elf.total()).sum()
+    }
+
+    fn percentile(&self, p: f64) -> Option<u32> {
+        if self.elves.is_empty() || !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+        let mut totals: Vec<u32> = self.elves.iter().map(|elf| elf.total()).collect();
+        totals.sort();
+        let rank = (p / 100.0 * totals.len() as f64).ceil() as usize;
+        Some(totals[rank.max(1) - 1])
+    }
+
+    fn carrying(&self, item: u32) -> Vec<&Elf> {
+        self.elves
+            .iter()
+            .filter(|elf| elf.items.contains(&item))
+            .collect()
+    }
+
+    fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "elf,item_count,total,items")?;
+        for elf in &self.elves {
+            let items: Vec<String> = elf.items.iter().map(|i| i.to_string()).collect();
+            writeln!(
+                out,
+                "{},{},{},{}",
+                elf.index,
+                elf.items.len(),
+                elf.total(),
+                items.join(";")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&mut FileLines> for Inventory {
+    type Error = io::Error;
+
+    fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
+        let mut elves = vec![];
+        let mut items = vec![];
+        for line in lines {
+            match line.as_str() {
+                "" => {
+                    let index = elves.len();
+                    elves.push(Elf { index, items });
+                    items = vec![];
+                }
+                x => items.push(parse_calories(x)?),
+            }
+        }
+        let index = elves.len();
+        elves.push(Elf { index, items });
+        Ok(Inventory { elves })
+    }
+}
+
 pub fn part_1() -> std::io::Result<u32> {
-    let elves = sort_by_calories(INPUT_FILE)?;
-    Ok(elves[0])
+    let inventory = Inventory::try_from(&mut FileLines::new(INPUT_FILE)?)?;
+    Ok(inventory.top_total(1))
 }
 
-fn _part_1_sample() -> std::io::Result<u32> {
-    let elves = sort_by_calories(_INPUT_FILE_SAMPLE)?;
-    Ok(elves[0])
+pub fn _part_1_sample() -> std::io::Result<u32> {
+    let inventory = Inventory::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
+    Ok(inventory.top_total(1))
 }
 
 pub fn part_2() -> std::io::Result<u32> {
-    let elves = sort_by_calories(INPUT_FILE)?;
-    Ok(elves[0] + elves[1] + elves[2])
+    let top = top_totals_streaming(FileLines::new(INPUT_FILE)?, 3)?;
+    Ok(top.iter().map(|(_, total)| total).sum())
 }
 
-fn _part_2_sample() -> std::io::Result<u32> {
-    let elves = sort_by_calories(_INPUT_FILE_SAMPLE)?;
-    Ok(elves[0] + elves[1] + elves[2])
+pub fn _part_2_sample() -> std::io::Result<u32> {
+    let top = top_totals_streaming(FileLines::new(_INPUT_FILE_SAMPLE)?, 3)?;
+    Ok(top.iter().map(|(_, total)| total).sum())
 }
 
-fn sort_by_calories(input_file: &str) -> std::io::Result<Vec<u32>> {
-    let mut calories: u32 = 0;
-    let mut elves: Vec<u32> = Vec::new();
+fn parse_calories(s: &str) -> io::Result<u32> {
+    match s.parse() {
+        Ok(calories) => Ok(calories),
+        Err(_) => error(&format!("Failed to parse calories: {}", s)),
+    }
+}
 
-    for line in FileLines::new(input_file)? {
+fn top_totals_streaming(
+    lines: impl Iterator<Item = String>,
+    n: usize,
+) -> io::Result<Vec<(usize, u32)>> {
+    let mut heap: BinaryHeap<Reverse<(u32, Reverse<usize>)>> = BinaryHeap::new();
+    let push = |heap: &mut BinaryHeap<_>, index: usize, total: u32| {
+        heap.push(Reverse((total, Reverse(index))));
+        if heap.len() > n {
+            heap.pop();
+        }
+    };
+    let mut index = 0;
+    let mut total = 0;
+    for line in lines {
         match line.as_str() {
             "" => {
-                elves.push(calories);
-                calories = 0;
+                push(&mut heap, index, total);
+                index += 1;
+                total = 0;
             }
-            x => {
-                calories += x.parse::<u32>().unwrap();
+            x => total += parse_calories(x)?,
+        }
+    }
+    push(&mut heap, index, total);
+    let mut top: Vec<(usize, u32)> = heap
+        .into_iter()
+        .map(|Reverse((total, Reverse(index)))| (index, total))
+        .collect();
+    top.sort_by_key(|&(index, total)| (Reverse(total), index));
+    Ok(top)
+}
+
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["top", n, rest @ ..] => {
+            let inventory = Inventory::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?)?;
+            for elf in inventory.top(arg(n)?) {
+                println!("{},{}", elf.index, elf.total());
+            }
+        }
+        ["stream-top", n, rest @ ..] => {
+            let lines = FileLines::new(file_arg(rest, INPUT_FILE))?;
+            for (index, total) in top_totals_streaming(lines, arg(n)?)? {
+                println!("{},{}", index, total);
+            }
+        }
+        ["percentile", p, rest @ ..] => {
+            let inventory = Inventory::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?)?;
+            match inventory.percentile(arg(p)?) {
+                Some(total) => println!("{}", total),
+                None => error("Percentile must be between 0 and 100")?,
+            }
+        }
+        ["carrying", item, rest @ ..] => {
+            let inventory = Inventory::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?)?;
+            for elf in inventory.carrying(arg(item)?) {
+                println!("{},{}", elf.index, elf.total());
             }
         }
+        ["csv", rest @ ..] => {
+            let inventory = Inventory::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?)?;
+            inventory.write_csv(&mut io::stdout())?;
+        }
+        _ => error("Usage: 1 (top|stream-top <n>|percentile <p>|carrying <item>|csv) [file]")?,
     }
-    elves.push(calories);
-    elves.sort_by(|a, b| b.cmp(a));
-    Ok(elves)
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    use super::{
+        _part_1_sample, _part_2_sample, part_1, part_2, top_totals_streaming, Elf, FileLines,
+        Inventory, _INPUT_FILE_SAMPLE,
+    };
+
+    fn sample() -> Inventory {
+        Inventory::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_top() {
+        let inventory = sample();
+        let top: Vec<usize> = inventory.top(4).iter().map(|elf| elf.index).collect();
+        assert_eq!(vec![3, 2, 4, 0], top);
+        assert_eq!(55000, inventory.top_total(5));
+        assert_eq!(55000, inventory.top_total(10));
+    }
+
+    #[test]
+    fn test_percentile() {
+        let inventory = sample();
+        assert_eq!(Some(4000), inventory.percentile(0.0));
+        assert_eq!(Some(10000), inventory.percentile(50.0));
+        assert_eq!(Some(24000), inventory.percentile(100.0));
+        assert_eq!(None, inventory.percentile(101.0));
+    }
+
+    #[test]
+    fn test_carrying() {
+        let inventory = sample();
+        let elves: Vec<usize> = inventory.carrying(8000).iter().map(|e| e.index).collect();
+        assert_eq!(vec![3], elves);
+        assert!(inventory.carrying(1234).is_empty());
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut out = vec![];
+        sample().write_csv(&mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(Some("elf,item_count,total,items"), lines.next());
+        assert_eq!(Some("0,3,6000,1000;2000;3000"), lines.next());
+        assert_eq!(Some("4,1,10000,10000"), lines.last());
+    }
+
+    #[test]
+    fn test_top_totals_streaming() {
+        let lines = FileLines::new(_INPUT_FILE_SAMPLE).unwrap();
+        let top = top_totals_streaming(lines, 2).unwrap();
+        assert_eq!(vec![(3, 24000), (2, 11000)], top);
+    }
+
+    #[test]
+    fn test_top_ties() {
+        let totals = [100, 200, 100, 200, 100];
+        let inventory = Inventory {
+            elves: totals
+                .iter()
+                .enumerate()
+                .map(|(index, total)| Elf {
+                    index,
+                    items: vec![*total],
+                })
+                .collect(),
+        };
+        let lines = totals.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        let lines = lines.join("\n\n");
+        for n in 1..=5 {
+            let top: Vec<(usize, u32)> = inventory
+                .top(n)
+                .iter()
+                .map(|elf| (elf.index, elf.total()))
+                .collect();
+            let streamed = top_totals_streaming(lines.lines().map(String::from), n).unwrap();
+            assert_eq!(top, streamed);
+        }
+    }
 
     #[test]
     fn test_part_1() {
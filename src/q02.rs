@@ -1,93 +1,299 @@
-use crate::parser::FileLines;
+use crate::parser::*;
+use std::collections::{HashMap, HashSet};
 use std::io;
+use std::str::FromStr;
 
 const INPUT_FILE: &str = "input/input02.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input02_sample.txt";
+const GAME_FILE: &str = "input/game02_standard.txt";
 
-struct Round<'a> {
-    opponent: &'a str,
-    mine: &'a str,
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Shape {
+    Rock,
+    Paper,
+    Scissors,
+    Lizard,
+    Spock,
 }
 
-impl<'a> Round<'a> {
-    fn score_1(self) -> u32 {
-        let item_score = match self.mine {
-            "X" => 1,
-            "Y" => 2,
-            "Z" => 3,
-            _ => 0,
-        };
-        let game_score = match (self.opponent, self.mine) {
-            ("A", "Z") | ("B", "X") | ("C", "Y") => 0,
-            ("A", "X") | ("B", "Y") | ("C", "Z") => 3,
-            ("A", "Y") | ("B", "Z") | ("C", "X") => 6,
-            _ => 0,
-        };
-        item_score + game_score
+impl FromStr for Shape {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Rock" => Ok(Shape::Rock),
+            "Paper" => Ok(Shape::Paper),
+            "Scissors" => Ok(Shape::Scissors),
+            "Lizard" => Ok(Shape::Lizard),
+            "Spock" => Ok(Shape::Spock),
+            _ => error(&format!("Unknown shape: {}", s)),
+        }
     }
+}
 
-    fn score_2(self) -> u32 {
-        let item_score = match (self.opponent, self.mine) {
-            ("A", "Y") | ("B", "X") | ("C", "Z") => 1,
-            ("A", "Z") | ("B", "Y") | ("C", "X") => 2,
-            ("A", "X") | ("B", "Z") | ("C", "Y") => 3,
-            _ => 0,
-        };
-        let game_score = match self.mine {
-            "X" => 0,
-            "Y" => 3,
-            "Z" => 6,
-            _ => 0,
-        };
-        item_score + game_score
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+impl FromStr for Outcome {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Lose" => Ok(Outcome::Lose),
+            "Draw" => Ok(Outcome::Draw),
+            "Win" => Ok(Outcome::Win),
+            _ => error(&format!("Unknown outcome: {}", s)),
+        }
     }
 }
 
-impl<'a> From<&'a str> for Round<'a> {
-    fn from(s: &'a str) -> Self {
-        let (x, y) = s.split_once(" ").unwrap();
-        Round {
-            opponent: x,
-            mine: y,
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Strategy {
+    Shape,
+    Outcome,
+    BestResponse,
+}
+
+#[derive(Debug, PartialEq)]
+struct Round {
+    opponent: Shape,
+    mine: Option<Shape>,
+    outcome: Option<Outcome>,
+}
+
+#[derive(Debug)]
+struct Game {
+    shape_scores: Vec<(Shape, u32)>,
+    outcome_scores: HashMap<Outcome, u32>,
+    beats: HashSet<(Shape, Shape)>,
+    opponent_codes: HashMap<String, Shape>,
+    shape_codes: HashMap<String, Shape>,
+    outcome_codes: HashMap<String, Outcome>,
+}
+
+impl Game {
+    fn outcome(&self, mine: Shape, opponent: Shape) -> Outcome {
+        if self.beats.contains(&(mine, opponent)) {
+            Outcome::Win
+        } else if self.beats.contains(&(opponent, mine)) {
+            Outcome::Lose
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    fn score(&self, mine: Shape, opponent: Shape) -> u32 {
+        let shape_score = self
+            .shape_scores
+            .iter()
+            .find(|(shape, _)| *shape == mine)
+            .map_or(0, |(_, score)| *score);
+        let outcome_score = self.outcome_scores[&self.outcome(mine, opponent)];
+        shape_score + outcome_score
+    }
+
+    fn shape_for(&self, opponent: Shape, outcome: Outcome) -> Option<Shape> {
+        self.shape_scores
+            .iter()
+            .map(|(shape, _)| *shape)
+            .filter(|shape| self.outcome(*shape, opponent) == outcome)
+            .max_by_key(|shape| self.score(*shape, opponent))
+    }
+
+    fn best_response(&self, opponent: Shape) -> Shape {
+        self.shape_scores
+            .iter()
+            .map(|(shape, _)| *shape)
+            .max_by_key(|shape| self.score(*shape, opponent))
+            .unwrap()
+    }
+
+    fn parse_round(&self, line: &str) -> io::Result<Round> {
+        match line.split_once(' ') {
+            Some((opponent, mine)) => match self.opponent_codes.get(opponent) {
+                Some(shape) => Ok(Round {
+                    opponent: *shape,
+                    mine: self.shape_codes.get(mine).copied(),
+                    outcome: self.outcome_codes.get(mine).copied(),
+                }),
+                None => error(&format!("Unknown opponent code: {}", opponent)),
+            },
+            None => error(&format!("Failed to parse round: {}", line)),
         }
     }
+
+    fn play(&self, round: &Round, strategy: Strategy) -> io::Result<u32> {
+        let mine = match strategy {
+            Strategy::Shape => round.mine,
+            Strategy::Outcome => round
+                .outcome
+                .and_then(|outcome| self.shape_for(round.opponent, outcome)),
+            Strategy::BestResponse => Some(self.best_response(round.opponent)),
+        };
+        match mine {
+            Some(shape) => Ok(self.score(shape, round.opponent)),
+            None => error(&format!("No {:?} response for {:?}", strategy, round)),
+        }
+    }
+
+    fn tournament(&self, guide: &[Round]) -> io::Result<Vec<(Strategy, u32)>> {
+        let mut results = vec![];
+        for strategy in [Strategy::Shape, Strategy::Outcome, Strategy::BestResponse] {
+            let mut total = 0;
+            for round in guide {
+                total += self.play(round, strategy)?;
+            }
+            results.push((strategy, total));
+        }
+        Ok(results)
+    }
 }
 
-pub fn part_1() -> io::Result<u32> {
-    Ok(do_part_1(FileLines::new(INPUT_FILE)?))
+impl TryFrom<&mut FileLines> for Game {
+    type Error = io::Error;
+
+    fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
+        let mut game = Game {
+            shape_scores: vec![],
+            outcome_scores: HashMap::new(),
+            beats: HashSet::new(),
+            opponent_codes: HashMap::new(),
+            shape_codes: HashMap::new(),
+            outcome_codes: HashMap::new(),
+        };
+        for line in lines {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[..] {
+                [] => {}
+                [w, ..] if w.starts_with('#') => {}
+                ["shape", name, score, opponent, mine] => {
+                    let shape = Shape::from_str(name)?;
+                    game.shape_scores.push((shape, arg(score)?));
+                    game.opponent_codes.insert(String::from(opponent), shape);
+                    game.shape_codes.insert(String::from(mine), shape);
+                }
+                ["beats", winner, loser] => {
+                    game.beats
+                        .insert((Shape::from_str(winner)?, Shape::from_str(loser)?));
+                }
+                ["outcome", name, score, code] => {
+                    let outcome = Outcome::from_str(name)?;
+                    game.outcome_scores.insert(outcome, arg(score)?);
+                    game.outcome_codes.insert(String::from(code), outcome);
+                }
+                _ => error(&format!("Failed to parse game rule: {}", line))?,
+            }
+        }
+        for outcome in [Outcome::Lose, Outcome::Draw, Outcome::Win] {
+            if !game.outcome_scores.contains_key(&outcome) {
+                error(&format!("Game has no score for {:?}", outcome))?;
+            }
+        }
+        Ok(game)
+    }
 }
 
-fn _part_1_sample() -> io::Result<u32> {
-    Ok(do_part_1(FileLines::new(_INPUT_FILE_SAMPLE)?))
+fn load(game_file: &str, input_file: &str) -> io::Result<(Game, Vec<Round>)> {
+    let game = Game::try_from(&mut FileLines::new(game_file)?)?;
+    let mut guide = vec![];
+    for line in FileLines::new(input_file)? {
+        guide.push(game.parse_round(&line)?);
+    }
+    Ok((game, guide))
 }
 
-fn do_part_1(lines: FileLines) -> u32 {
+fn total_score(game_file: &str, input_file: &str, strategy: Strategy) -> io::Result<u32> {
+    let (game, guide) = load(game_file, input_file)?;
     let mut score = 0;
-    for line in lines {
-        score += Round::from(line.as_str()).score_1();
+    for round in &guide {
+        score += game.play(round, strategy)?;
     }
-    score
+    Ok(score)
+}
+
+pub fn part_1() -> io::Result<u32> {
+    total_score(GAME_FILE, INPUT_FILE, Strategy::Shape)
+}
+
+pub fn _part_1_sample() -> io::Result<u32> {
+    total_score(GAME_FILE, _INPUT_FILE_SAMPLE, Strategy::Shape)
 }
 
 pub fn part_2() -> io::Result<u32> {
-    Ok(do_part_2(FileLines::new(INPUT_FILE)?))
+    total_score(GAME_FILE, INPUT_FILE, Strategy::Outcome)
 }
 
-fn _part_2_sample() -> io::Result<u32> {
-    Ok(do_part_2(FileLines::new(_INPUT_FILE_SAMPLE)?))
+pub fn _part_2_sample() -> io::Result<u32> {
+    total_score(GAME_FILE, _INPUT_FILE_SAMPLE, Strategy::Outcome)
 }
 
-fn do_part_2(lines: FileLines) -> u32 {
-    let mut score = 0;
-    for line in lines {
-        score += Round::from(line.as_str()).score_2();
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["tournament", rest @ ..] => {
+            let game_file = file_arg(rest, GAME_FILE);
+            let input_file = file_arg(rest.get(1..).unwrap_or(&[]), INPUT_FILE);
+            let (game, guide) = load(game_file, input_file)?;
+            for (strategy, score) in game.tournament(&guide)? {
+                println!("{:?}: {}", strategy, score);
+            }
+        }
+        ["best-response", rest @ ..] => {
+            let game_file = file_arg(rest, GAME_FILE);
+            let input_file = file_arg(rest.get(1..).unwrap_or(&[]), INPUT_FILE);
+            let (game, guide) = load(game_file, input_file)?;
+            for round in guide {
+                let mine = game.best_response(round.opponent);
+                let score = game.score(mine, round.opponent);
+                println!("{:?} -> {:?} ({})", round.opponent, mine, score);
+            }
+        }
+        _ => error("Usage: 2 (tournament|best-response) [game file] [guide file]")?,
     }
-    score
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    use super::{
+        _part_1_sample, _part_2_sample, load, part_1, part_2, Outcome, Shape, Strategy,
+        _INPUT_FILE_SAMPLE,
+    };
+
+    const GAME_FILE_RPSLS: &str = "input/game02_rpsls.txt";
+
+    #[test]
+    fn test_rpsls() {
+        let (game, guide) = load(GAME_FILE_RPSLS, _INPUT_FILE_SAMPLE).unwrap();
+        assert_eq!(Outcome::Win, game.outcome(Shape::Spock, Shape::Rock));
+        assert_eq!(Outcome::Lose, game.outcome(Shape::Lizard, Shape::Rock));
+        assert_eq!(Outcome::Draw, game.outcome(Shape::Lizard, Shape::Lizard));
+        assert_eq!(
+            Some(Shape::Spock),
+            game.shape_for(Shape::Rock, Outcome::Win)
+        );
+        assert_eq!(Shape::Lizard, guide[0].mine.unwrap());
+        assert_eq!(Outcome::Draw, guide[0].outcome.unwrap());
+        assert_eq!(
+            vec![
+                (Strategy::Shape, 4 + 9 + 11),
+                (Strategy::Outcome, 4 + 5 + 11),
+                (Strategy::BestResponse, 11 + 10 + 11),
+            ],
+            game.tournament(&guide).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_best_response() {
+        let (game, guide) = load(super::GAME_FILE, _INPUT_FILE_SAMPLE).unwrap();
+        assert_eq!(Shape::Paper, game.best_response(Shape::Rock));
+        assert_eq!(Shape::Rock, game.best_response(Shape::Scissors));
+        assert_eq!(8 + 9 + 7, game.tournament(&guide).unwrap()[2].1);
+    }
 
     #[test]
     fn test_part_1() {
@@ -1,106 +1,225 @@
 use crate::parser::*;
-use std::collections::HashSet;
 use std::io;
 
 const INPUT_FILE: &str = "input/input03.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input03_sample.txt";
+const STANDARD_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
 
-struct Group {
-    bag_1: HashSet<u8>,
-    bag_2: HashSet<u8>,
-    bag_3: HashSet<u8>,
+struct Alphabet {
+    positions: [Option<u8>; 256],
+    symbols: Vec<u8>,
 }
 
-impl TryFrom<&mut FileSplit> for Group {
-    type Error = io::Error;
+impl Alphabet {
+    fn new(symbols: &str) -> io::Result<Self> {
+        if !symbols.is_ascii() {
+            return error("Alphabet symbols must be ASCII");
+        }
+        if symbols.len() > 128 {
+            return error("Alphabet can have at most 128 symbols");
+        }
+        let mut positions = [None; 256];
+        for (i, b) in symbols.bytes().enumerate() {
+            if positions[b as usize].is_some() {
+                return error(&format!("Duplicate symbol in alphabet: {}", b as char));
+            }
+            positions[b as usize] = Some(i as u8);
+        }
+        Ok(Alphabet {
+            positions,
+            symbols: symbols.bytes().collect(),
+        })
+    }
 
-    fn try_from(lines: &mut FileSplit) -> Result<Self, Self::Error> {
-        let l_1 = lines.next_result()?;
-        let l_2 = lines.next_result()?;
-        let l_3 = lines.next_result()?;
+    fn priority(&self, item: u8) -> Option<u32> {
+        self.positions[item as usize].map(|p| u32::from(p) + 1)
+    }
+
+    fn item_set(&self, items: &[u8]) -> io::Result<ItemSet> {
+        let mut set = ItemSet(0);
+        for item in items {
+            match self.positions[*item as usize] {
+                Some(p) => set.0 |= 1 << p,
+                None => error(&format!("Item not in alphabet: {}", *item as char))?,
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ItemSet(u128);
+
+impl ItemSet {
+    fn intersect(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    fn positions(self) -> impl Iterator<Item = usize> {
+        (0..128).filter(move |p| self.0 & (1 << p) != 0)
+    }
+}
+
+struct Group {
+    bags: Vec<ItemSet>,
+}
+
+impl Group {
+    fn from_compartments(line: &str, alphabet: &Alphabet) -> io::Result<Self> {
+        let (compartment_1, compartment_2) = line.as_bytes().split_at(line.len() / 2);
         Ok(Group {
-            bag_1: HashSet::from_iter(l_1),
-            bag_2: HashSet::from_iter(l_2),
-            bag_3: HashSet::from_iter(l_3),
+            bags: vec![
+                alphabet.item_set(compartment_1)?,
+                alphabet.item_set(compartment_2)?,
+            ],
         })
     }
-}
 
-impl Iterator for FileInput<FileSplit, Group> {
-    type Item = Group;
+    fn shared(&self) -> ItemSet {
+        self.bags
+            .iter()
+            .fold(ItemSet(u128::MAX), |shared, bag| shared.intersect(*bag))
+    }
+
+    fn shared_items(&self, alphabet: &Alphabet) -> Vec<char> {
+        self.shared()
+            .positions()
+            .map(|p| alphabet.symbols[p] as char)
+            .collect()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        parse_from(&mut self.source)
+    fn priority(&self) -> u32 {
+        self.shared().positions().map(|p| p as u32 + 1).sum()
+    }
+}
+
+fn parse_groups(split: &mut FileSplit, size: usize, alphabet: &Alphabet) -> io::Result<Vec<Group>> {
+    if size == 0 {
+        return error("Group size must be positive");
+    }
+    let mut groups = vec![];
+    loop {
+        let mut bags = vec![];
+        for _ in 0..size {
+            match split.next_result() {
+                Ok(items) => bags.push(alphabet.item_set(&items)?),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => break,
+                Err(e) => return Err(e),
+            }
+        }
+        match bags.len() {
+            0 => return Ok(groups),
+            n if n == size => groups.push(Group { bags }),
+            n => return error(&format!("Last group has {} of {} rucksacks", n, size)),
+        }
     }
 }
 
 pub fn part_1() -> io::Result<u32> {
-    Ok(do_part_1(FileLines::new(INPUT_FILE)?))
+    do_part_1(FileLines::new(INPUT_FILE)?)
 }
 
-fn _part_1_sample() -> io::Result<u32> {
-    Ok(do_part_1(FileLines::new(_INPUT_FILE_SAMPLE)?))
+pub fn _part_1_sample() -> io::Result<u32> {
+    do_part_1(FileLines::new(_INPUT_FILE_SAMPLE)?)
 }
 
-fn do_part_1(lines: FileLines) -> u32 {
+fn do_part_1(lines: FileLines) -> io::Result<u32> {
+    let alphabet = Alphabet::new(STANDARD_ALPHABET)?;
     let mut sum = 0;
     for line in lines {
-        let mut compartment_1 = line;
-        let compartment_2 = compartment_1.split_off(compartment_1.len() / 2);
-        let set_1: HashSet<&u8> = HashSet::from_iter(compartment_1.as_bytes());
-        let set_2: HashSet<&u8> = HashSet::from_iter(compartment_2.as_bytes());
-        for b in set_1 {
-            if set_2.contains(b) {
-                sum += convert_to_priority(b);
-            }
-        }
+        sum += Group::from_compartments(&line, &alphabet)?.priority();
     }
-    sum
+    Ok(sum)
 }
 
 pub fn part_2() -> Result<u32, io::Error> {
     do_part_2(INPUT_FILE)
 }
 
-fn _part_2_sample() -> Result<u32, io::Error> {
+pub fn _part_2_sample() -> Result<u32, io::Error> {
     do_part_2(_INPUT_FILE_SAMPLE)
 }
 
 fn do_part_2(input_file: &str) -> Result<u32, io::Error> {
-    let mut sum = 0;
-    for group in parse_bytes::<Group>(input_file, &b'\n')? {
-        for item in group.bag_1 {
-            if group.bag_2.contains(&item) && group.bag_3.contains(&item) {
-                sum += convert_to_priority(&item);
-            }
-        }
-    }
-    Ok(sum)
+    let alphabet = Alphabet::new(STANDARD_ALPHABET)?;
+    let groups = parse_groups(&mut FileSplit::new(input_file, &b'\n')?, 3, &alphabet)?;
+    Ok(groups.iter().map(|group| group.priority()).sum())
 }
 
-fn convert_to_priority(byte: &u8) -> u32 {
-    match *byte {
-        x if x <= 90 => u32::from(x) - 38,
-        x => u32::from(x) - 96,
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["shared", size, rest @ ..] => {
+            let alphabet =
+                Alphabet::new(file_arg(rest.get(1..).unwrap_or(&[]), STANDARD_ALPHABET))?;
+            let mut split = FileSplit::new(file_arg(rest, INPUT_FILE), &b'\n')?;
+            for (i, group) in parse_groups(&mut split, arg(size)?, &alphabet)?
+                .iter()
+                .enumerate()
+            {
+                let items: Vec<String> = group
+                    .shared_items(&alphabet)
+                    .into_iter()
+                    .map(|c| format!("{}={}", c, alphabet.priority(c as u8).unwrap_or(0)))
+                    .collect();
+                println!("{}: {} ({})", i, items.join(" "), group.priority());
+            }
+        }
+        _ => error("Usage: 3 shared <group size> [file] [alphabet]")?,
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, convert_to_priority, part_1, part_2};
+    use super::{
+        _part_1_sample, _part_2_sample, parse_groups, part_1, part_2, Alphabet, FileSplit,
+        _INPUT_FILE_SAMPLE, STANDARD_ALPHABET,
+    };
 
     #[test]
     fn test_convert_to_priority() {
-        assert_eq!(1, convert_to_priority(&97)); // a
-        assert_eq!(26, convert_to_priority(&122)); // z
-        assert_eq!(27, convert_to_priority(&65)); // A
-        assert_eq!(52, convert_to_priority(&90)); // Z
-        assert_eq!(16, convert_to_priority(&112)); // p
-        assert_eq!(38, convert_to_priority(&76)); // L
-        assert_eq!(42, convert_to_priority(&80)); // P
-        assert_eq!(22, convert_to_priority(&118)); // v
-        assert_eq!(20, convert_to_priority(&116)); // t
-        assert_eq!(19, convert_to_priority(&115)); // s
+        let alphabet = Alphabet::new(STANDARD_ALPHABET).unwrap();
+        assert_eq!(Some(1), alphabet.priority(b'a'));
+        assert_eq!(Some(26), alphabet.priority(b'z'));
+        assert_eq!(Some(27), alphabet.priority(b'A'));
+        assert_eq!(Some(52), alphabet.priority(b'Z'));
+        assert_eq!(Some(16), alphabet.priority(b'p'));
+        assert_eq!(Some(38), alphabet.priority(b'L'));
+        assert_eq!(Some(42), alphabet.priority(b'P'));
+        assert_eq!(Some(22), alphabet.priority(b'v'));
+        assert_eq!(Some(20), alphabet.priority(b't'));
+        assert_eq!(Some(19), alphabet.priority(b's'));
+        assert_eq!(None, alphabet.priority(b'!'));
+    }
+
+    #[test]
+    fn test_alphabet() {
+        assert!(Alphabet::new("abca").is_err());
+        let e = Alphabet::new("éè").err().unwrap().to_string();
+        assert_eq!("Alphabet symbols must be ASCII", e);
+        let alphabet = Alphabet::new("zyx").unwrap();
+        assert_eq!(Some(1), alphabet.priority(b'z'));
+        assert!(alphabet.item_set(b"xyzw").is_err());
+    }
+
+    #[test]
+    fn test_group_sizes() {
+        let alphabet = Alphabet::new(STANDARD_ALPHABET).unwrap();
+        let mut split = FileSplit::new(_INPUT_FILE_SAMPLE, &b'\n').unwrap();
+        let groups = parse_groups(&mut split, 3, &alphabet).unwrap();
+        assert_eq!(vec!['r'], groups[0].shared_items(&alphabet));
+        assert_eq!(vec!['Z'], groups[1].shared_items(&alphabet));
+
+        let mut split = FileSplit::new(_INPUT_FILE_SAMPLE, &b'\n').unwrap();
+        let groups = parse_groups(&mut split, 2, &alphabet).unwrap();
+        assert_eq!(3, groups.len());
+        assert_eq!(
+            vec!['f', 'r', 's', 'F', 'M'],
+            groups[0].shared_items(&alphabet)
+        );
+
+        let mut split = FileSplit::new(_INPUT_FILE_SAMPLE, &b'\n').unwrap();
+        assert!(parse_groups(&mut split, 4, &alphabet).is_err());
     }
 
     #[test]
@@ -1,3 +1,4 @@
+use crate::interval::*;
 use crate::parser::*;
 use std::io;
 
@@ -5,21 +6,21 @@ const INPUT_FILE: &str = "input/input04.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input04_sample.txt";
 
 struct Input {
-    elf_1: (u32, u32),
-    elf_2: (u32, u32),
+    elf_1: IntervalSet,
+    elf_2: IntervalSet,
 }
 
 impl Input {
     fn range_fully_contained(&self) -> bool {
-        let (s1, e1) = self.elf_1;
-        let (s2, e2) = self.elf_2;
-        (s1 <= s2 && e1 >= e2) || (s1 >= s2 && e1 <= e2)
+        self.elf_1.is_superset(&self.elf_2) || self.elf_2.is_superset(&self.elf_1)
     }
 
     fn has_any_overlap(&self) -> bool {
-        let (s1, e1) = self.elf_1;
-        let (s2, e2) = self.elf_2;
-        (s1 <= s2 && s2 <= e1) || (s2 <= s1 && s1 <= e2)
+        !self.elf_1.intersection(&self.elf_2).is_empty()
+    }
+
+    fn sections(&self) -> IntervalSet {
+        self.elf_1.union(&self.elf_2)
     }
 }
 
@@ -28,13 +29,20 @@ impl TryFrom<&mut FileLines> for Input {
 
     fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
         let line = lines.next_result()?;
-        let (elf_1, elf_2) = line.split_once(',').unwrap();
-        let (elf_1_start, elf_1_end) = elf_1.split_once('-').unwrap();
-        let (elf_2_start, elf_2_end) = elf_2.split_once('-').unwrap();
-        Ok(Input {
-            elf_1: (elf_1_start.parse().unwrap(), elf_1_end.parse().unwrap()),
-            elf_2: (elf_2_start.parse().unwrap(), elf_2_end.parse().unwrap()),
-        })
+        match line.split_once(',') {
+            Some((elf_1, elf_2)) => Ok(Input {
+                elf_1: IntervalSet::from_iter([parse_interval(elf_1)?]),
+                elf_2: IntervalSet::from_iter([parse_interval(elf_2)?]),
+            }),
+            None => error(&format!("Failed to parse elf pair: {}", line)),
+        }
+    }
+}
+
+fn parse_interval(s: &str) -> io::Result<Interval> {
+    match s.split_once('-') {
+        Some((start, end)) => Ok((arg(start)?, arg(end)?)),
+        None => error(&format!("Failed to parse section range: {}", s)),
     }
 }
 
@@ -50,7 +58,7 @@ pub fn part_1() -> io::Result<u32> {
     do_part_1(INPUT_FILE)
 }
 
-fn _part_1_sample() -> io::Result<u32> {
+pub fn _part_1_sample() -> io::Result<u32> {
     do_part_1(_INPUT_FILE_SAMPLE)
 }
 
@@ -68,7 +76,7 @@ pub fn part_2() -> io::Result<u32> {
     do_part_2(INPUT_FILE)
 }
 
-fn _part_2_sample() -> io::Result<u32> {
+pub fn _part_2_sample() -> io::Result<u32> {
     do_part_2(_INPUT_FILE_SAMPLE)
 }
 
@@ -82,9 +90,72 @@ fn do_part_2(input_file: &str) -> io::Result<u32> {
     Ok(count)
 }
 
+fn assignments(input_file: &str) -> io::Result<Vec<Interval>> {
+    let mut intervals = vec![];
+    for elf_pair in parse_lines::<Input>(input_file)? {
+        intervals.extend(elf_pair.elf_1.intervals());
+        intervals.extend(elf_pair.elf_2.intervals());
+    }
+    Ok(intervals)
+}
+
+fn uncovered(assignments: &[Interval]) -> IntervalSet {
+    let covered: IntervalSet = assignments.iter().copied().collect();
+    match (covered.intervals().first(), covered.intervals().last()) {
+        (Some((start, _)), Some((_, end))) => {
+            IntervalSet::from_iter([(*start, *end)]).difference(&covered)
+        }
+        _ => IntervalSet::new(),
+    }
+}
+
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["uncovered", rest @ ..] => {
+            let uncovered = uncovered(&assignments(file_arg(rest, INPUT_FILE))?);
+            println!(
+                "{:?} ({} sections)",
+                uncovered.intervals(),
+                uncovered.coverage()
+            );
+        }
+        ["at-least", k, rest @ ..] => {
+            let covered = covered_at_least(&assignments(file_arg(rest, INPUT_FILE))?, arg(k)?);
+            println!(
+                "{:?} ({} sections)",
+                covered.intervals(),
+                covered.coverage()
+            );
+        }
+        ["covering", section, rest @ ..] => {
+            let section = arg(section)?;
+            for (i, elf_pair) in parse_lines::<Input>(file_arg(rest, INPUT_FILE))?.enumerate() {
+                if elf_pair.sections().contains(section) {
+                    println!("{}: {:?}", i, elf_pair.sections().intervals());
+                }
+            }
+        }
+        _ => error("Usage: 4 (uncovered|at-least <k>|covering <section>) [file]")?,
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    use super::{
+        _part_1_sample, _part_2_sample, assignments, covered_at_least, part_1, part_2, uncovered,
+        _INPUT_FILE_SAMPLE,
+    };
+
+    #[test]
+    fn test_coverage_queries() {
+        let assignments = assignments(_INPUT_FILE_SAMPLE).unwrap();
+        assert_eq!(vec![(2, 9)], covered_at_least(&assignments, 1).intervals());
+        assert_eq!(vec![(3, 7)], covered_at_least(&assignments, 5).intervals());
+        assert_eq!(vec![(6, 6)], covered_at_least(&assignments, 8).intervals());
+        assert!(uncovered(&assignments).is_empty());
+        assert_eq!(vec![(5, 5)], uncovered(&[(2, 4), (6, 8)]).intervals());
+    }
 
     #[test]
     fn test_part_1() {
@@ -1,121 +1,284 @@
+mod crane;
+mod replay;
+
 use crate::parser::*;
+use crane::*;
+use replay::Replay;
 use std::io;
 
 const INPUT_FILE: &str = "input/input05.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input05_sample.txt";
 
 #[derive(Debug, PartialEq)]
-struct Move(usize, usize, usize);
+struct Move {
+    count: usize,
+    from: usize,
+    to: usize,
+    line: usize,
+}
 
 #[derive(Debug, PartialEq)]
 struct Input {
+    labels: Vec<String>,
     crates: Vec<Vec<char>>,
     moves: Vec<Move>,
 }
 
+impl Input {
+    fn validate(&self, m: &Move, crates: &[Vec<char>]) -> io::Result<()> {
+        let available = crates[m.from].len();
+        if available < m.count {
+            return error(&format!(
+                "Line {}: cannot move {} crates from stack {} which holds {}",
+                m.line, m.count, self.labels[m.from], available
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl TryFrom<&mut FileLines> for Input {
     type Error = io::Error;
 
     fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
-        let mut crates: Vec<Vec<char>> = vec![vec![]; 9];
-        let mut moves: Vec<Move> = vec![];
-        while let Some(line) = lines.next() {
-            if line == "" {
+        let mut drawing: Vec<String> = vec![];
+        for line in lines.by_ref() {
+            if line.is_empty() {
                 break;
             }
-            let mut stack = 0;
-            let mut chars = line.chars();
-            while let Some(c) = chars.next() {
-                match c {
-                    ' ' => {
-                        chars.next();
-                        chars.next();
-                        chars.next();
-                    }
-                    '[' => {
-                        let item = chars.next().unwrap();
-                        chars.next();
-                        chars.next();
-                        crates[stack].insert(0, item);
-                    }
-                    _ => {}
-                }
-                stack += 1;
-            }
+            drawing.push(line);
         }
-        for line in lines {
-            let parts = line.split(' ').collect::<Vec<_>>();
-            moves.push(Move(
-                parts[1].parse::<usize>().unwrap(),
-                parts[3].parse::<usize>().unwrap(),
-                parts[5].parse::<usize>().unwrap(),
-            ));
+        let (labels, crates) = match drawing.split_last() {
+            Some((footer, rows)) => parse_drawing(footer, rows)?,
+            None => error("Missing stack drawing")?,
+        };
+        let mut moves: Vec<Move> = vec![];
+        for (i, line) in lines.enumerate() {
+            let line_number = drawing.len() + i + 2;
+            let stack = |label: &str| match labels.iter().position(|l| l == label) {
+                Some(index) => Ok(index),
+                None => error(&format!(
+                    "Line {}: no stack labelled {}",
+                    line_number, label
+                )),
+            };
+            match line.split(' ').collect::<Vec<_>>()[..] {
+                ["move", count, "from", from, "to", to] => moves.push(Move {
+                    count: arg(count)?,
+                    from: stack(from)?,
+                    to: stack(to)?,
+                    line: line_number,
+                }),
+                _ => error(&format!(
+                    "Line {}: failed to parse move: {}",
+                    line_number, line
+                ))?,
+            }
         }
         Ok(Input {
-            crates: crates,
-            moves: moves,
+            labels,
+            crates,
+            moves,
         })
     }
 }
 
+fn parse_drawing(footer: &str, rows: &[String]) -> io::Result<(Vec<String>, Vec<Vec<char>>)> {
+    let mut labels: Vec<(String, usize, usize)> = vec![];
+    let mut start = None;
+    for (i, c) in footer.char_indices().chain([(footer.len(), ' ')]) {
+        match (c, start) {
+            (' ', Some(s)) => {
+                labels.push((String::from(&footer[s..i]), s, i - 1));
+                start = None;
+            }
+            (' ', None) => {}
+            ('[' | ']', _) => error(&format!("Stack drawing has no label line: {}", footer))?,
+            (_, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if labels.is_empty() {
+        return error("Stack drawing has no labels");
+    }
+    let mut crates: Vec<Vec<char>> = vec![vec![]; labels.len()];
+    for row in rows.iter().rev() {
+        for (i, c) in row.char_indices() {
+            if c != '[' {
+                continue;
+            }
+            let column = i + 1;
+            let stack = (0..labels.len())
+                .min_by_key(|&l| {
+                    let (_, s, e) = labels[l];
+                    s.saturating_sub(column) + column.saturating_sub(e)
+                })
+                .unwrap();
+            match row[column..].chars().next() {
+                Some(item) if item != ']' => crates[stack].push(item),
+                _ => error(&format!("Failed to parse crate in row: {}", row))?,
+            }
+        }
+    }
+    Ok((labels.into_iter().map(|(l, _, _)| l).collect(), crates))
+}
+
 pub fn part_1() -> io::Result<String> {
     let input = Input::try_from(&mut FileLines::new(INPUT_FILE)?)?;
-    Ok(do_part_1(input))
+    do_part_1(input)
 }
 
-fn _part_1_sample() -> io::Result<String> {
+pub fn _part_1_sample() -> io::Result<String> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
-    Ok(do_part_1(input))
+    do_part_1(input)
 }
 
-fn do_part_1(input: Input) -> String {
-    let mut crates = input.crates;
-    for m in &input.moves {
-        let (count, from, to) = (m.0, m.1, m.2);
-        for _ in 0..count {
-            let item = crates[from - 1].pop().unwrap();
-            crates[to - 1].push(item);
-        }
-    }
-    let mut result = String::from("");
-    for c in crates {
-        if let Some(item) = c.last() {
-            result += item.to_string().as_str();
-        }
-    }
-    String::from(result)
+fn do_part_1(input: Input) -> io::Result<String> {
+    let mut replay = Replay::new(&input, &CrateMover9000);
+    replay.run()?;
+    Ok(replay.tops())
 }
 
 pub fn part_2() -> io::Result<String> {
     let input = Input::try_from(&mut FileLines::new(INPUT_FILE)?)?;
-    Ok(do_part_2(input))
+    do_part_2(input)
 }
 
-fn _part_2_sample() -> io::Result<String> {
+pub fn _part_2_sample() -> io::Result<String> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
-    Ok(do_part_2(input))
+    do_part_2(input)
+}
+
+fn do_part_2(input: Input) -> io::Result<String> {
+    let mut replay = Replay::new(&input, &CrateMover9001);
+    replay.run()?;
+    Ok(replay.tops())
+}
+
+fn crane_arg(name: &str) -> io::Result<Box<dyn Crane>> {
+    match name.split_once(':') {
+        None if name == "9000" => Ok(Box::new(CrateMover9000)),
+        None if name == "9001" => Ok(Box::new(CrateMover9001)),
+        None if name == "bottom" => Ok(Box::new(BottomCrane)),
+        Some(("capacity", n)) => Ok(Box::new(CapacityCrane(arg(n)?))),
+        _ => error(&format!("Unknown crane: {}", name)),
+    }
 }
 
-fn do_part_2(input: Input) -> String {
-    let mut crates = input.crates;
-    for m in &input.moves {
-        let (count, from, to) = (m.0, m.1, m.2);
-        let from_crate = &mut crates[from - 1];
-        let items = &mut from_crate.split_off(from_crate.len() - count);
-        crates[to - 1].append(items);
-    }
-    let mut result = String::from("");
-    for c in crates {
-        if let Some(item) = c.last() {
-            result += item.to_string().as_str();
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["replay", crane, rest @ ..] => {
+            let input = Input::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?)?;
+            let crane = crane_arg(crane)?;
+            let mut replay = Replay::new(&input, crane.as_ref());
+            print!("{}", replay.render());
+            while let Some(description) = replay.describe(replay.position()) {
+                replay.step()?;
+                println!("\n{}\n", description);
+                print!("{}", replay.render());
+            }
+            println!("\n{}", replay.tops());
+        }
+        ["render", crane, step, rest @ ..] => {
+            let input = Input::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?)?;
+            let crane = crane_arg(crane)?;
+            let mut replay = Replay::new(&input, crane.as_ref());
+            replay.run()?;
+            let step: usize = arg(step)?;
+            while replay.position() > step && replay.undo() {}
+            print!("{}", replay.render());
         }
+        _ => error("Usage: 5 (replay <crane>|render <crane> <step>) [file]; crane is 9000, 9001, bottom or capacity:<n>")?,
     }
-    String::from(result)
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    use super::crane::*;
+    use super::replay::Replay;
+    use super::{
+        _part_1_sample, _part_2_sample, do_part_1, part_1, part_2, FileLines, Input,
+        _INPUT_FILE_SAMPLE,
+    };
+
+    const INPUT_FILE_SAMPLE_WIDE: &str = "input/input05_sample_wide.txt";
+    const INPUT_FILE_SAMPLE_INVALID: &str = "input/input05_sample_invalid.txt";
+
+    #[test]
+    fn test_wide_drawing() {
+        let input = Input::try_from(&mut FileLines::new(INPUT_FILE_SAMPLE_WIDE).unwrap()).unwrap();
+        assert_eq!(12, input.labels.len());
+        assert_eq!("12", input.labels[11]);
+        assert_eq!(vec!['Z', 'N'], input.crates[0]);
+        assert_eq!(vec!['K', 'X'], input.crates[11]);
+        assert_eq!(10, input.moves[1].from);
+        assert_eq!("XFDABEGHIJY", do_part_1(input).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_move() {
+        let input =
+            Input::try_from(&mut FileLines::new(INPUT_FILE_SAMPLE_INVALID).unwrap()).unwrap();
+        let message = do_part_1(input).unwrap_err().to_string();
+        assert_eq!(
+            "Line 7: cannot move 3 crates from stack 2 which holds 2",
+            message
+        );
+    }
+
+    #[test]
+    fn test_cranes() {
+        let lift = |crane: &dyn Crane, count| {
+            let mut stack = vec!['A', 'B', 'C', 'D', 'E'];
+            let items = crane.lift(&mut stack, count);
+            (stack, items)
+        };
+        assert_eq!(
+            (vec!['A', 'B'], vec!['E', 'D', 'C']),
+            lift(&CrateMover9000, 3)
+        );
+        assert_eq!(
+            (vec!['A', 'B'], vec!['C', 'D', 'E']),
+            lift(&CrateMover9001, 3)
+        );
+        assert_eq!(
+            (vec!['A'], vec!['D', 'E', 'B', 'C']),
+            lift(&CapacityCrane(2), 4)
+        );
+        assert_eq!((vec!['D', 'E'], vec!['A', 'B', 'C']), lift(&BottomCrane, 3));
+    }
+
+    #[test]
+    fn test_replay() {
+        let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE).unwrap()).unwrap();
+        let mut replay = Replay::new(&input, &CrateMover9000);
+        let initial = replay.render();
+        assert_eq!(
+            "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n",
+            initial
+        );
+        assert!(replay.step().unwrap());
+        assert_eq!("DCP", replay.tops());
+        replay.run().unwrap();
+        assert_eq!(4, replay.position());
+        assert!(!replay.step().unwrap());
+        assert_eq!("CMZ", replay.tops());
+        while replay.undo() {}
+        assert_eq!(0, replay.position());
+        assert_eq!(initial, replay.render());
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let input = Input::try_from(&mut FileLines::new(INPUT_FILE_SAMPLE_WIDE).unwrap()).unwrap();
+        let replay = Replay::new(&input, &CrateMover9001);
+        let drawing = std::fs::read_to_string(INPUT_FILE_SAMPLE_WIDE).unwrap();
+        let expected: Vec<&str> = drawing.lines().take(3).map(|l| l.trim_end()).collect();
+        let rendered = replay.render();
+        let actual: Vec<&str> = rendered.lines().map(|l| l.trim_end()).collect();
+        assert_eq!(expected, actual);
+    }
 
     #[test]
     fn test_part_1() {
@@ -0,0 +1,59 @@
+pub trait Crane {
+    fn name(&self) -> String;
+    fn lift(&self, stack: &mut Vec<char>, count: usize) -> Vec<char>;
+}
+
+#[derive(Debug)]
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9000")
+    }
+
+    fn lift(&self, stack: &mut Vec<char>, count: usize) -> Vec<char> {
+        let mut items = stack.split_off(stack.len() - count);
+        items.reverse();
+        items
+    }
+}
+
+#[derive(Debug)]
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9001")
+    }
+
+    fn lift(&self, stack: &mut Vec<char>, count: usize) -> Vec<char> {
+        stack.split_off(stack.len() - count)
+    }
+}
+
+#[derive(Debug)]
+pub struct CapacityCrane(pub usize);
+
+impl Crane for CapacityCrane {
+    fn name(&self) -> String {
+        format!("CapacityCrane({})", self.0)
+    }
+
+    fn lift(&self, stack: &mut Vec<char>, count: usize) -> Vec<char> {
+        let items = stack.split_off(stack.len() - count);
+        items.rchunks(self.0.max(1)).flatten().copied().collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct BottomCrane;
+
+impl Crane for BottomCrane {
+    fn name(&self) -> String {
+        String::from("BottomCrane")
+    }
+
+    fn lift(&self, stack: &mut Vec<char>, count: usize) -> Vec<char> {
+        stack.drain(..count).collect()
+    }
+}
@@ -0,0 +1,102 @@
+use super::crane::Crane;
+use super::Input;
+use std::io;
+
+pub struct Replay<'a> {
+    input: &'a Input,
+    crane: &'a dyn Crane,
+    history: Vec<Vec<Vec<char>>>,
+}
+
+impl<'a> Replay<'a> {
+    pub fn new(input: &'a Input, crane: &'a dyn Crane) -> Self {
+        Self {
+            input,
+            crane,
+            history: vec![input.crates.clone()],
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.history.len() - 1
+    }
+
+    pub fn current(&self) -> &Vec<Vec<char>> {
+        self.history.last().unwrap()
+    }
+
+    pub fn step(&mut self) -> io::Result<bool> {
+        let m = match self.input.moves.get(self.position()) {
+            Some(m) => m,
+            None => return Ok(false),
+        };
+        let mut crates = self.current().clone();
+        self.input.validate(m, &crates)?;
+        let items = self.crane.lift(&mut crates[m.from], m.count);
+        crates[m.to].extend(items);
+        self.history.push(crates);
+        Ok(true)
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+        while self.step()? {}
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> bool {
+        if self.history.len() > 1 {
+            self.history.pop();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn tops(&self) -> String {
+        self.current().iter().filter_map(|c| c.last()).collect()
+    }
+
+    pub fn render(&self) -> String {
+        let crates = self.current();
+        let width = self
+            .input
+            .labels
+            .iter()
+            .map(|l| l.len() + 1)
+            .max()
+            .unwrap_or(0)
+            .max(3);
+        let height = crates.iter().map(|c| c.len()).max().unwrap_or(0);
+        let mut lines = vec![];
+        for row in (0..height).rev() {
+            let cells: Vec<String> = crates
+                .iter()
+                .map(|c| match c.get(row) {
+                    Some(item) => format!("{:width$}", format!("[{}]", item)),
+                    None => " ".repeat(width),
+                })
+                .collect();
+            lines.push(cells.join(" "));
+        }
+        let labels: Vec<String> = self
+            .input
+            .labels
+            .iter()
+            .map(|l| format!(" {:width$}", l, width = width - 1))
+            .collect();
+        lines.push(labels.join(" "));
+        lines.join("\n") + "\n"
+    }
+
+    pub fn describe(&self, step: usize) -> Option<String> {
+        self.input.moves.get(step).map(|m| {
+            format!(
+                "move {} from {} to {} ({})",
+                m.count,
+                self.input.labels[m.from],
+                self.input.labels[m.to],
+                self.crane.name()
+            )
+        })
+    }
+}
@@ -1,107 +1,227 @@
 use crate::parser::*;
-use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs::File;
 use std::io;
+use std::io::prelude::*;
 
 const INPUT_FILE: &str = "input/input06.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input06_sample.txt";
 
-pub fn part_1() -> io::Result<u32> {
-    Ok(do_part_1(read_line(INPUT_FILE)?))
+struct MarkerDetector {
+    window: usize,
+    buf: VecDeque<u8>,
+    counts: [usize; 256],
+    duplicates: usize,
+    position: usize,
 }
 
-fn _part_1_sample() -> io::Result<u32> {
-    Ok(do_part_1(read_line(_INPUT_FILE_SAMPLE)?))
-}
+impl MarkerDetector {
+    fn new(window: usize) -> Self {
+        Self {
+            window,
+            buf: VecDeque::with_capacity(window + 1),
+            counts: [0; 256],
+            duplicates: 0,
+            position: 0,
+        }
+    }
 
-fn do_part_1(line: String) -> u32 {
-    let mut stream = line.into_bytes().into_iter();
-    start_of_packet(&mut stream)
-}
+    fn reset(&mut self) {
+        *self = Self::new(self.window);
+    }
 
-pub fn part_2() -> io::Result<u32> {
-    Ok(do_part_2(read_line(INPUT_FILE)?))
+    fn push(&mut self, byte: u8) -> Option<usize> {
+        self.position += 1;
+        self.buf.push_back(byte);
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.duplicates += 1;
+        }
+        if self.buf.len() > self.window {
+            let old = self.buf.pop_front().unwrap();
+            self.counts[old as usize] -= 1;
+            if self.counts[old as usize] == 1 {
+                self.duplicates -= 1;
+            }
+        }
+        if self.buf.len() == self.window && self.duplicates == 0 {
+            Some(self.position)
+        } else {
+            None
+        }
+    }
 }
 
-fn _part_2_sample() -> io::Result<u32> {
-    Ok(do_part_2(read_line(_INPUT_FILE_SAMPLE)?))
+struct Markers<R: Read> {
+    bytes: io::Bytes<io::BufReader<R>>,
+    detectors: Vec<MarkerDetector>,
+    line: usize,
+    found: VecDeque<(usize, usize, usize)>,
 }
 
-fn do_part_2(line: String) -> u32 {
-    let mut stream = line.into_bytes().into_iter();
-    start_of_message(&mut stream)
+impl<R: Read> Markers<R> {
+    fn new(reader: R, windows: &[usize]) -> Self {
+        Self {
+            bytes: io::BufReader::new(reader).bytes(),
+            detectors: windows.iter().map(|w| MarkerDetector::new(*w)).collect(),
+            line: 1,
+            found: VecDeque::new(),
+        }
+    }
 }
 
-fn find_in_stream(stream: &mut impl Iterator<Item = u8>, distinct: usize) -> u32 {
-    let mut count = 0;
-    let mut buf: Vec<u8> = vec![];
-    let mut char_counts: HashMap<u8, usize> = HashMap::new();
-    while let Some(new) = stream.next() {
-        count += 1;
-        buf.insert(0, new);
-        char_counts.insert(new, char_counts.get(&new).unwrap_or(&0) + 1);
-        if buf.len() < distinct {
-            continue;
-        } else if buf.len() > distinct {
-            let old = buf.pop().unwrap();
-            char_counts.insert(old, char_counts.get(&old).unwrap_or(&0) - 1);
-        }
-        if buf
-            .iter()
-            .filter(|x| char_counts.get(x).unwrap_or(&0) != &1)
-            .next()
-            .is_none()
-        {
-            break;
+impl<R: Read> Iterator for Markers<R> {
+    type Item = io::Result<(usize, usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.found.is_empty() {
+            let byte = match self.bytes.next()? {
+                Ok(b'\r') => continue,
+                Ok(b'\n') => {
+                    self.line += 1;
+                    self.detectors.iter_mut().for_each(MarkerDetector::reset);
+                    continue;
+                }
+                Ok(byte) => byte,
+                Err(e) => return Some(Err(e)),
+            };
+            for detector in self.detectors.iter_mut() {
+                if let Some(position) = detector.push(byte) {
+                    self.found.push_back((self.line, detector.window, position));
+                }
+            }
         }
+        self.found.pop_front().map(Ok)
+    }
+}
+
+fn first_marker(reader: impl Read, window: usize) -> io::Result<u32> {
+    match Markers::new(reader, &[window]).next() {
+        Some(marker) => Ok(marker?.2 as u32),
+        None => error(&format!("No marker of length {} found", window)),
     }
-    count
 }
 
-fn read_line(input_file: &str) -> io::Result<String> {
-    let mut lines = FileLines::new(input_file)?;
-    Ok(String::from(lines.next().unwrap()))
+pub fn part_1() -> io::Result<u32> {
+    start_of_packet(File::open(INPUT_FILE)?)
+}
+
+pub fn _part_1_sample() -> io::Result<u32> {
+    start_of_packet(File::open(_INPUT_FILE_SAMPLE)?)
+}
+
+pub fn part_2() -> io::Result<u32> {
+    start_of_message(File::open(INPUT_FILE)?)
+}
+
+pub fn _part_2_sample() -> io::Result<u32> {
+    start_of_message(File::open(_INPUT_FILE_SAMPLE)?)
 }
 
-fn start_of_message(stream: &mut impl Iterator<Item = u8>) -> u32 {
-    find_in_stream(stream, 14)
+fn start_of_message(reader: impl Read) -> io::Result<u32> {
+    first_marker(reader, 14)
 }
 
-fn start_of_packet(stream: &mut impl Iterator<Item = u8>) -> u32 {
-    find_in_stream(stream, 4)
+fn start_of_packet(reader: impl Read) -> io::Result<u32> {
+    first_marker(reader, 4)
+}
+
+fn windows_arg(windows: &str) -> io::Result<Vec<usize>> {
+    let mut sizes = vec![];
+    for window in windows.split(',') {
+        match arg(window)? {
+            0 => error("Marker windows must be at least 1")?,
+            size => sizes.push(size),
+        }
+    }
+    Ok(sizes)
+}
+
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["markers", windows, rest @ ..] => {
+            let sizes = windows_arg(windows)?;
+            let reader: Box<dyn Read> = match file_arg(rest, INPUT_FILE) {
+                "-" => Box::new(io::stdin()),
+                input_file => Box::new(File::open(input_file)?),
+            };
+            for marker in Markers::new(reader, &sizes) {
+                let (line, window, position) = marker?;
+                println!("{},{},{}", line, window, position);
+            }
+        }
+        _ => error("Usage: 6 markers <window>[,<window>...] [file|-]")?,
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
         _part_1_sample, _part_2_sample, part_1, part_2, start_of_message, start_of_packet,
+        windows_arg, Markers,
     };
 
+    #[test]
+    fn test_windows_arg() {
+        assert_eq!(vec![4, 14], windows_arg("4,14").unwrap());
+        assert!(windows_arg("0").is_err());
+        assert!(windows_arg("4,0").is_err());
+        assert!(windows_arg("four").is_err());
+    }
+
+    #[test]
+    fn test_all_markers() {
+        let stream = "abcabcdd\nab".as_bytes();
+        let markers: Vec<(usize, usize, usize)> =
+            Markers::new(stream, &[3, 4]).map(|m| m.unwrap()).collect();
+        assert_eq!(
+            vec![
+                (1, 3, 3),
+                (1, 3, 4),
+                (1, 3, 5),
+                (1, 3, 6),
+                (1, 3, 7),
+                (1, 4, 7)
+            ],
+            markers
+        );
+    }
+
+    #[test]
+    fn test_markers_per_line() {
+        let stream = "aab\r\ncdd\nxyz".as_bytes();
+        let markers: Vec<(usize, usize, usize)> =
+            Markers::new(stream, &[3]).map(|m| m.unwrap()).collect();
+        assert_eq!(vec![(3, 3, 3)], markers);
+    }
+
     #[test]
     fn test_start_of_message() {
         let s1 = String::from("mjqjpqmgbljsphdztnvjfqwrcgsmlb");
-        assert_eq!(19, start_of_message(&mut s1.into_bytes().into_iter()));
+        assert_eq!(19, start_of_message(s1.as_bytes()).unwrap());
         let s2 = String::from("bvwbjplbgvbhsrlpgdmjqwftvncz");
-        assert_eq!(23, start_of_message(&mut s2.into_bytes().into_iter()));
+        assert_eq!(23, start_of_message(s2.as_bytes()).unwrap());
         let s3 = String::from("nppdvjthqldpwncqszvftbrmjlhg");
-        assert_eq!(23, start_of_message(&mut s3.into_bytes().into_iter()));
+        assert_eq!(23, start_of_message(s3.as_bytes()).unwrap());
         let s4 = String::from("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg");
-        assert_eq!(29, start_of_message(&mut s4.into_bytes().into_iter()));
+        assert_eq!(29, start_of_message(s4.as_bytes()).unwrap());
         let s5 = String::from("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw");
-        assert_eq!(26, start_of_message(&mut s5.into_bytes().into_iter()));
+        assert_eq!(26, start_of_message(s5.as_bytes()).unwrap());
     }
 
     #[test]
     fn test_start_of_packet() {
         let s1 = String::from("mjqjpqmgbljsphdztnvjfqwrcgsmlb");
-        assert_eq!(7, start_of_packet(&mut s1.into_bytes().into_iter()));
+        assert_eq!(7, start_of_packet(s1.as_bytes()).unwrap());
         let s2 = String::from("bvwbjplbgvbhsrlpgdmjqwftvncz");
-        assert_eq!(5, start_of_packet(&mut s2.into_bytes().into_iter()));
+        assert_eq!(5, start_of_packet(s2.as_bytes()).unwrap());
         let s3 = String::from("nppdvjthqldpwncqszvftbrmjlhg");
-        assert_eq!(6, start_of_packet(&mut s3.into_bytes().into_iter()));
+        assert_eq!(6, start_of_packet(s3.as_bytes()).unwrap());
         let s4 = String::from("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg");
-        assert_eq!(10, start_of_packet(&mut s4.into_bytes().into_iter()));
+        assert_eq!(10, start_of_packet(s4.as_bytes()).unwrap());
         let s5 = String::from("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw");
-        assert_eq!(11, start_of_packet(&mut s5.into_bytes().into_iter()));
+        assert_eq!(11, start_of_packet(s5.as_bytes()).unwrap());
     }
 
     #[test]
@@ -1,154 +1,560 @@
+mod transcript;
+
 use crate::parser::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
 use std::io;
+use std::path::Path;
+use transcript::*;
 
 const INPUT_FILE: &str = "input/input07.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input07_sample.txt";
+const TOTAL_SPACE: u64 = 70_000_000;
+const NEEDED_SPACE: u64 = 30_000_000;
+
+#[derive(Debug, Default, PartialEq)]
+struct Dir {
+    dirs: BTreeMap<String, Dir>,
+    files: BTreeMap<String, u64>,
+    size: u64,
+}
+
+impl Dir {
+    fn lookup(&self, path: &str) -> Option<&Dir> {
+        let mut dir = self;
+        for name in path.split('/').filter(|n| !n.is_empty()) {
+            dir = dir.dirs.get(name)?;
+        }
+        Some(dir)
+    }
+
+    fn dir_mut(&mut self, path: &[String]) -> Option<&mut Dir> {
+        let mut dir = self;
+        for name in path {
+            dir = dir.dirs.get_mut(name)?;
+        }
+        Some(dir)
+    }
+
+    fn create_dir(&mut self, path: &[String]) -> Result<(), String> {
+        let mut dir = self;
+        for (i, name) in path.iter().enumerate() {
+            if dir.files.contains_key(name) {
+                return Err(format!("/{}", path[..=i].join("/")));
+            }
+            dir = dir.dirs.entry(name.clone()).or_default();
+        }
+        Ok(())
+    }
+
+    fn add_file(&mut self, path: &[String], name: &str, size: u64) {
+        let mut dir = self;
+        dir.size += size;
+        for dir_name in path {
+            dir = dir.dirs.get_mut(dir_name).unwrap();
+            dir.size += size;
+        }
+        dir.files.insert(String::from(name), size);
+    }
+
+    fn walk(&self) -> Vec<(String, &Dir)> {
+        let mut result = vec![];
+        self.walk_from(String::from("/"), &mut result);
+        result
+    }
+
+    fn walk_from<'a>(&'a self, path: String, result: &mut Vec<(String, &'a Dir)>) {
+        result.push((path.clone(), self));
+        for (name, dir) in &self.dirs {
+            dir.walk_from(join(&path, name), result);
+        }
+    }
+
+    fn du(&self) -> Vec<(String, u64)> {
+        self.walk()
+            .into_iter()
+            .map(|(path, dir)| (path, dir.size))
+            .collect()
+    }
+
+    fn find_larger(&self, threshold: u64) -> Vec<(String, u64)> {
+        let mut result = vec![];
+        for (path, dir) in self.walk() {
+            for (name, size) in &dir.files {
+                if *size > threshold {
+                    result.push((join(&path, name), *size));
+                }
+            }
+        }
+        result
+    }
+
+    fn smallest_freeing(&self, total: u64, needed: u64) -> Option<(String, u64)> {
+        let free = total.saturating_sub(self.size);
+        self.du()
+            .into_iter()
+            .filter(|(_, size)| free + size >= needed)
+            .min_by_key(|(_, size)| *size)
+    }
+
+    fn tree(&self, name: &str) -> String {
+        let mut output = format!("- {} (dir, size={})\n", name, self.size);
+        self.tree_from(1, &mut output);
+        output
+    }
+
+    fn tree_from(&self, depth: usize, output: &mut String) {
+        let mut entries: Vec<(&String, Option<&Dir>, u64)> = vec![];
+        entries.extend(self.dirs.iter().map(|(n, d)| (n, Some(d), d.size)));
+        entries.extend(self.files.iter().map(|(n, s)| (n, None, *s)));
+        entries.sort_by_key(|(name, _, _)| *name);
+        for (name, dir, size) in entries {
+            let kind = if dir.is_some() { "dir" } else { "file" };
+            let indent = "  ".repeat(depth);
+            output.push_str(&format!("{}- {} ({}, size={})\n", indent, name, kind, size));
+            if let Some(dir) = dir {
+                dir.tree_from(depth + 1, output);
+            }
+        }
+    }
+}
 
-#[derive(Debug)]
-enum Node {
-    Dir(Option<usize>, String, Vec<usize>),
-    File(usize, String, u32),
+fn join(path: &str, name: &str) -> String {
+    format!("{}/{}", path.trim_end_matches('/'), name)
 }
 
-impl Node {
-    fn size(&self, all_nodes: &Vec<Node>) -> u32 {
+#[derive(Debug, PartialEq)]
+enum TranscriptError {
+    UnknownCommand {
+        line: usize,
+        command: String,
+    },
+    Malformed {
+        line: usize,
+        text: String,
+    },
+    OutputWithoutLs {
+        line: usize,
+    },
+    AboveRoot {
+        line: usize,
+    },
+    Conflict {
+        line: usize,
+        path: String,
+    },
+    InconsistentSize {
+        line: usize,
+        path: String,
+        listed: u64,
+        relisted: u64,
+    },
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Node::File(_, _, size) => *size,
-            Node::Dir(_, _, children) => children
-                .iter()
-                .map(|x| &all_nodes[*x])
-                .map(|x| x.size(all_nodes))
-                .sum(),
+            TranscriptError::UnknownCommand { line, command } => {
+                write!(f, "Line {}: unknown command: {}", line, command)
+            }
+            TranscriptError::Malformed { line, text } => {
+                write!(f, "Line {}: failed to parse: {}", line, text)
+            }
+            TranscriptError::OutputWithoutLs { line } => {
+                write!(f, "Line {}: output outside of ls", line)
+            }
+            TranscriptError::AboveRoot { line } => {
+                write!(f, "Line {}: cd above the root directory", line)
+            }
+            TranscriptError::Conflict { line, path } => {
+                write!(f, "Line {}: {} is both a file and a directory", line, path)
+            }
+            TranscriptError::InconsistentSize {
+                line,
+                path,
+                listed,
+                relisted,
+            } => write!(
+                f,
+                "Line {}: {} was listed with size {} but now has size {}",
+                line, path, listed, relisted
+            ),
         }
     }
 }
 
-struct Input {
-    nodes: Vec<Node>,
+impl Error for TranscriptError {}
+
+impl From<TranscriptError> for io::Error {
+    fn from(e: TranscriptError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
 }
 
-impl TryFrom<&mut FileLines> for Input {
-    type Error = io::Error;
+fn resolve(cwd: &[String], target: &str) -> Option<Vec<String>> {
+    let mut path = if target.starts_with('/') {
+        vec![]
+    } else {
+        cwd.to_vec()
+    };
+    for name in target.split('/') {
+        match name {
+            "" | "." => {}
+            ".." => {
+                path.pop()?;
+            }
+            name => path.push(String::from(name)),
+        }
+    }
+    Some(path)
+}
 
-    fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
-        let mut all_nodes = Vec::<Node>::new();
-        all_nodes.push(Node::Dir(None, String::from("/"), Vec::new()));
-        let mut current_dir = 0;
-        while let Some(line) = lines.next() {
-            match line {
-                s if s == "$ ls" => {}
-                s if s == "$ cd /" => current_dir = 0,
-                s if s == "$ cd .." => {
-                    current_dir = match all_nodes[current_dir] {
-                        Node::Dir(Some(n), _, _) => n,
-                        Node::File(n, _, _) => n,
-                        Node::Dir(None, _, _) => {
-                            return Err(io::Error::new(
-                                io::ErrorKind::Other,
-                                "Tried to cd .. from root directory",
-                            ));
-                        }
-                    }
-                }
-                s if s.starts_with("$ cd") => {
-                    let dir_name = s.rsplit_once(' ').unwrap().1;
-                    current_dir = match &all_nodes[current_dir] {
-                        Node::Dir(_, _, children) => *children
-                            .iter()
-                            .filter(|child| match &all_nodes[**child] {
-                                Node::Dir(_, name, _) => name == dir_name,
-                                _ => false,
-                            })
-                            .next()
-                            .unwrap(),
-                        _ => current_dir,
-                    }
+struct Input {
+    root: Dir,
+}
+
+impl Input {
+    fn parse(lines: impl Iterator<Item = String>) -> Result<Input, TranscriptError> {
+        let mut root = Dir::default();
+        let mut cwd: Vec<String> = vec![];
+        let mut listing = false;
+        for (i, text) in lines.enumerate() {
+            let line = i + 1;
+            let malformed = || TranscriptError::Malformed {
+                line,
+                text: text.clone(),
+            };
+            let words: Vec<&str> = text.split_whitespace().collect();
+            listing &= words.first() != Some(&"$");
+            match words[..] {
+                ["$", "ls"] => listing = true,
+                ["$", "cd", target] => {
+                    cwd = resolve(&cwd, target).ok_or(TranscriptError::AboveRoot { line })?;
+                    root.create_dir(&cwd)
+                        .map_err(|path| TranscriptError::Conflict { line, path })?;
                 }
-                s if s.starts_with("dir") => {
-                    let (_, dir_name) = s.split_once(' ').unwrap();
-                    let dir_node = Node::Dir(Some(current_dir), String::from(dir_name), Vec::new());
-                    add_child(&mut all_nodes, current_dir, dir_node);
+                ["$", ..] => Err(TranscriptError::UnknownCommand {
+                    line,
+                    command: words[1..].join(" "),
+                })?,
+                [_, _] if !listing => Err(TranscriptError::OutputWithoutLs { line })?,
+                ["dir", name] => {
+                    let mut path = cwd.clone();
+                    path.push(String::from(name));
+                    root.create_dir(&path)
+                        .map_err(|path| TranscriptError::Conflict { line, path })?;
                 }
-                s => {
-                    let (size_str, file_name) = s.split_once(' ').unwrap();
-                    let size: u32 = size_str.parse().unwrap();
-                    let file_node = Node::File(current_dir, String::from(file_name), size);
-                    add_child(&mut all_nodes, current_dir, file_node);
+                [size, name] => {
+                    let relisted = size.parse().map_err(|_| malformed())?;
+                    let path = join(&format!("/{}", cwd.join("/")), name);
+                    let dir = root.dir_mut(&cwd).unwrap();
+                    match dir.files.get(name) {
+                        _ if dir.dirs.contains_key(name) => {
+                            Err(TranscriptError::Conflict { line, path })?
+                        }
+                        Some(&listed) if listed != relisted => {
+                            Err(TranscriptError::InconsistentSize {
+                                line,
+                                path,
+                                listed,
+                                relisted,
+                            })?
+                        }
+                        Some(_) => {}
+                        None => root.add_file(&cwd, name, relisted),
+                    }
                 }
+                _ => Err(malformed())?,
             }
         }
-        Ok(Input { nodes: all_nodes })
+        Ok(Input { root })
     }
 }
 
-pub fn part_1() -> io::Result<u32> {
+impl TryFrom<&mut FileLines> for Input {
+    type Error = io::Error;
+
+    fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
+        Ok(Input::parse(lines)?)
+    }
+}
+
+pub fn part_1() -> io::Result<u64> {
     let input = Input::try_from(&mut FileLines::new(INPUT_FILE)?)?;
     Ok(do_part_1(input))
 }
 
-fn _part_1_sample() -> io::Result<u32> {
+pub fn _part_1_sample() -> io::Result<u64> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_1(input))
 }
 
-fn do_part_1(input: Input) -> u32 {
-    let sizes = get_directory_sizes(&input.nodes);
-    sizes.iter().filter(|x| **x <= 100_000).sum()
+fn do_part_1(input: Input) -> u64 {
+    input
+        .root
+        .du()
+        .iter()
+        .map(|(_, size)| *size)
+        .filter(|size| *size <= 100_000)
+        .sum()
 }
 
-pub fn part_2() -> io::Result<u32> {
+pub fn part_2() -> io::Result<u64> {
     let input = Input::try_from(&mut FileLines::new(INPUT_FILE)?)?;
     Ok(do_part_2(input))
 }
 
-fn _part_2_sample() -> io::Result<u32> {
+pub fn _part_2_sample() -> io::Result<u64> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_2(input))
 }
 
-fn do_part_2(input: Input) -> u32 {
-    const TOTAL_SPACE: u32 = 70_000_000;
-    const NEEDED_SPACE: u32 = 30_000_000;
-    let free_space = TOTAL_SPACE - input.nodes[0].size(&input.nodes);
-    let mut sizes = get_directory_sizes(&input.nodes);
-    sizes.sort();
-    for size in sizes {
-        if size + free_space >= NEEDED_SPACE {
-            return size;
-        }
+fn do_part_2(input: Input) -> u64 {
+    match input.root.smallest_freeing(TOTAL_SPACE, NEEDED_SPACE) {
+        Some((_, size)) => size,
+        None => 0,
     }
-    0
 }
 
-fn add_child(nodes: &mut Vec<Node>, parent: usize, child: Node) {
-    let i = nodes.len();
-    match nodes[parent] {
-        Node::Dir(_, _, ref mut children) => {
-            children.push(i);
-            nodes.push(child);
-        }
-        _ => {}
+type DirSizes = Vec<(String, u64)>;
+
+fn order_arg(args: &[&str]) -> io::Result<Order> {
+    match args.first().copied().unwrap_or("dfs") {
+        "dfs" => Ok(Order::DepthFirst),
+        order => match order.strip_prefix("random:") {
+            Some(seed) => Ok(Order::Random(arg(seed)?)),
+            None => error(&format!("Unknown order: {}", order)),
+        },
     }
 }
 
-fn add_sizes_from(nodes: &Vec<Node>, node: usize, sizes: &mut Vec<u32>) {
-    if let Node::Dir(_, _, ref children) = nodes[node] {
-        sizes.push(nodes[node].size(nodes));
-        for child in children.iter() {
-            add_sizes_from(nodes, *child, sizes);
-        }
-    }
+fn round_trip(dir: &Path, order: Order) -> io::Result<(DirSizes, DirSizes)> {
+    let input = Input::parse(generate(dir, order)?.into_iter())?;
+    Ok((directory_sizes(dir)?, input.root.du()))
 }
 
-fn get_directory_sizes(nodes: &Vec<Node>) -> Vec<u32> {
-    let mut sizes = Vec::<u32>::new();
-    add_sizes_from(&nodes, 0, &mut sizes);
-    sizes
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    let load = |rest: &[&str]| Input::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?);
+    match args {
+        ["du", rest @ ..] => {
+            for (path, size) in load(rest)?.root.du() {
+                println!("{}\t{}", size, path);
+            }
+        }
+        ["find", threshold, rest @ ..] => {
+            for (path, size) in load(rest)?.root.find_larger(arg(threshold)?) {
+                println!("{}\t{}", size, path);
+            }
+        }
+        ["tree", path, rest @ ..] => match load(rest)?.root.lookup(path) {
+            Some(dir) => print!("{}", dir.tree(path)),
+            None => error(&format!("No such directory: {}", path))?,
+        },
+        ["free", total, needed, rest @ ..] => {
+            match load(rest)?.root.smallest_freeing(arg(total)?, arg(needed)?) {
+                Some((path, size)) => println!("{}\t{}", size, path),
+                None => error("No single directory frees enough space")?,
+            }
+        }
+        ["generate", dir, rest @ ..] => {
+            for line in generate(Path::new(dir), order_arg(rest)?)? {
+                println!("{}", line);
+            }
+        }
+        ["round-trip", dir, rest @ ..] => {
+            let (expected, actual) = round_trip(Path::new(dir), order_arg(rest)?)?;
+            if expected != actual {
+                error("Transcript sizes do not match the directory")?;
+            }
+            println!("{} directories match", actual.len());
+        }
+        _ => error(
+            "Usage: 7 (du|find <size>|tree <path>|free <total> <needed>) [file] \
+             | (generate|round-trip) <dir> [dfs|random:<seed>]",
+        )?,
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    use super::{
+        _part_1_sample, _part_2_sample, part_1, part_2, round_trip, FileLines, Input, Order,
+        TranscriptError, _INPUT_FILE_SAMPLE,
+    };
+    use std::env;
+    use std::fs;
+
+    fn sample() -> Input {
+        Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_du() {
+        let du = sample().root.du();
+        assert_eq!(
+            vec![
+                (String::from("/"), 48381165),
+                (String::from("/a"), 94853),
+                (String::from("/a/e"), 584),
+                (String::from("/d"), 24933642),
+            ],
+            du
+        );
+    }
+
+    #[test]
+    fn test_lookup_and_find() {
+        let input = sample();
+        assert_eq!(Some(584), input.root.lookup("/a/e").map(|d| d.size));
+        assert_eq!(Some(584), input.root.lookup("a/e/").map(|d| d.size));
+        assert!(input.root.lookup("/a/x").is_none());
+        assert_eq!(
+            vec![
+                (String::from("/b.txt"), 14848514),
+                (String::from("/c.dat"), 8504156),
+                (String::from("/d/d.log"), 8033020),
+            ],
+            input.root.find_larger(8_000_000)
+        );
+    }
+
+    #[test]
+    fn test_smallest_freeing() {
+        let input = sample();
+        assert_eq!(
+            Some((String::from("/a"), 94853)),
+            input.root.smallest_freeing(48_400_000, 100_000)
+        );
+        assert_eq!(None, input.root.smallest_freeing(70_000_000, 80_000_000));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = env::temp_dir().join(format!("q07_round_trip_{}", std::process::id()));
+        fs::create_dir_all(dir.join("a/b/c")).unwrap();
+        fs::create_dir_all(dir.join("d")).unwrap();
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        fs::write(dir.join("top.txt"), "12345").unwrap();
+        fs::write(dir.join("a/x"), "1").unwrap();
+        fs::write(dir.join("a/b/c/y.dat"), vec![0; 4096]).unwrap();
+        fs::write(dir.join("d/z"), vec![0; 100]).unwrap();
+
+        let (expected, actual) = round_trip(&dir, Order::DepthFirst).unwrap();
+        assert_eq!(expected, actual);
+        assert_eq!((String::from("/"), 4202), actual[0]);
+        assert_eq!(6, actual.len());
+        for seed in 1..20 {
+            let (expected, actual) = round_trip(&dir, Order::Random(seed)).unwrap();
+            assert_eq!(expected, actual);
+        }
+
+        fs::write(dir.join("d/two words"), "1").unwrap();
+        let e = round_trip(&dir, Order::DepthFirst).unwrap_err().to_string();
+        assert!(e.contains("two words"), "{}", e);
+        fs::remove_file(dir.join("d/two words")).unwrap();
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.join("top.txt"), dir.join("a/link")).unwrap();
+            let e = round_trip(&dir, Order::DepthFirst).unwrap_err().to_string();
+            assert!(e.contains("not a file or directory"), "{}", e);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn parse(lines: &[&str]) -> Result<Input, TranscriptError> {
+        Input::parse(lines.iter().map(|l| String::from(*l)))
+    }
+
+    #[test]
+    fn test_paths_and_relisting() {
+        let input = parse(&[
+            "$ cd /a/b/c",
+            "$ ls",
+            "10 x",
+            "$ cd ../../d",
+            "$ ls",
+            "dir e",
+            "5 y",
+            "$ cd /a",
+            "$ ls",
+            "dir b",
+            "dir d",
+            "$ cd b/c",
+            "$ ls",
+            "10 x",
+            "$ cd /a/./d/",
+            "$ ls",
+            "5 y",
+        ])
+        .unwrap();
+        assert_eq!(
+            vec![
+                (String::from("/"), 15),
+                (String::from("/a"), 15),
+                (String::from("/a/b"), 10),
+                (String::from("/a/b/c"), 10),
+                (String::from("/a/d"), 5),
+                (String::from("/a/d/e"), 0),
+            ],
+            input.root.du()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Some(TranscriptError::UnknownCommand {
+                line: 2,
+                command: String::from("rm -rf x"),
+            }),
+            parse(&["$ cd /", "$ rm -rf x"]).err()
+        );
+        assert_eq!(
+            Some(TranscriptError::InconsistentSize {
+                line: 5,
+                path: String::from("/a/x"),
+                listed: 10,
+                relisted: 11,
+            }),
+            parse(&["$ cd a", "$ ls", "10 x", "$ ls", "11 x"]).err()
+        );
+        assert_eq!(
+            Some(TranscriptError::Conflict {
+                line: 4,
+                path: String::from("/x"),
+            }),
+            parse(&["$ ls", "10 x", "$ cd /", "$ cd x"]).err()
+        );
+        assert_eq!(
+            Some(TranscriptError::AboveRoot { line: 2 }),
+            parse(&["$ cd a", "$ cd ../.."]).err()
+        );
+        assert_eq!(
+            Some(TranscriptError::OutputWithoutLs { line: 2 }),
+            parse(&["$ cd a", "10 x"]).err()
+        );
+        assert_eq!(
+            Some(TranscriptError::Malformed {
+                line: 2,
+                text: String::from("ten x"),
+            }),
+            parse(&["$ ls", "ten x"]).err()
+        );
+    }
+
+    #[test]
+    fn test_tree() {
+        let tree = sample().root.tree("/");
+        let mut lines = tree.lines();
+        assert_eq!(Some("- / (dir, size=48381165)"), lines.next());
+        assert_eq!(Some("  - a (dir, size=94853)"), lines.next());
+        assert_eq!(Some("    - e (dir, size=584)"), lines.next());
+        assert_eq!(Some("      - i (file, size=584)"), lines.next());
+        assert_eq!(Some("    - f (file, size=29116)"), lines.next());
+        assert_eq!(14, tree.lines().count());
+    }
 
     #[test]
     fn test_part_1() {
@@ -0,0 +1,153 @@
+use crate::parser::*;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub enum Order {
+    DepthFirst,
+    Random(u64),
+}
+
+struct Entry {
+    name: String,
+    size: Option<u64>,
+}
+
+fn entries(dir: &Path) -> io::Result<Vec<Entry>> {
+    let mut result = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = match entry.file_name().into_string() {
+            Ok(name) if !name.contains(char::is_whitespace) => name,
+            _ => error(&format!(
+                "Cannot write {} in a transcript: unsupported name",
+                entry.path().display()
+            ))?,
+        };
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            result.push(Entry { name, size: None });
+        } else if file_type.is_file() {
+            let size = entry.metadata()?.len();
+            result.push(Entry {
+                name,
+                size: Some(size),
+            });
+        } else {
+            error(&format!(
+                "Cannot write {} in a transcript: not a file or directory",
+                entry.path().display()
+            ))?;
+        }
+    }
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(result)
+}
+
+fn listing(entries: &[Entry]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|e| match e.size {
+            Some(size) => format!("{} {}", size, e.name),
+            None => format!("dir {}", e.name),
+        })
+        .collect()
+}
+
+pub fn generate(root: &Path, order: Order) -> io::Result<Vec<String>> {
+    let mut lines = vec![String::from("$ cd /")];
+    match order {
+        Order::DepthFirst => depth_first(root, &mut lines)?,
+        Order::Random(seed) => random(root, &mut Rng(seed.max(1)), &mut lines)?,
+    }
+    Ok(lines)
+}
+
+fn depth_first(dir: &Path, lines: &mut Vec<String>) -> io::Result<()> {
+    let entries = entries(dir)?;
+    lines.push(String::from("$ ls"));
+    lines.extend(listing(&entries));
+    for entry in entries.iter().filter(|e| e.size.is_none()) {
+        lines.push(format!("$ cd {}", entry.name));
+        depth_first(&dir.join(&entry.name), lines)?;
+        lines.push(String::from("$ cd .."));
+    }
+    Ok(())
+}
+
+fn random(root: &Path, rng: &mut Rng, lines: &mut Vec<String>) -> io::Result<()> {
+    let mut cwd: Vec<String> = vec![];
+    let mut unexplored: Vec<Vec<String>> = vec![vec![]];
+    while !unexplored.is_empty() {
+        let target = unexplored.swap_remove(rng.below(unexplored.len()));
+        let mut common = cwd.iter().zip(&target).take_while(|(a, b)| a == b).count();
+        if rng.below(4) == 0 {
+            lines.push(String::from("$ cd /"));
+            common = 0;
+        } else {
+            for _ in common..cwd.len() {
+                lines.push(String::from("$ cd .."));
+            }
+        }
+        for name in &target[common..] {
+            lines.push(format!("$ cd {}", name));
+        }
+        cwd = target;
+
+        let mut entries = entries(&cwd.iter().fold(root.to_path_buf(), |p, n| p.join(n)))?;
+        rng.shuffle(&mut entries);
+        lines.push(String::from("$ ls"));
+        lines.extend(listing(&entries));
+        for entry in entries.iter().filter(|e| e.size.is_none()) {
+            let mut child = cwd.clone();
+            child.push(entry.name.clone());
+            unexplored.push(child);
+        }
+    }
+    Ok(())
+}
+
+pub fn directory_sizes(root: &Path) -> io::Result<Vec<(String, u64)>> {
+    let mut result = vec![];
+    sizes_from(root, String::from("/"), &mut result)?;
+    Ok(result)
+}
+
+fn sizes_from(dir: &Path, path: String, result: &mut Vec<(String, u64)>) -> io::Result<u64> {
+    let index = result.len();
+    result.push((path.clone(), 0));
+    let mut total = 0;
+    for entry in entries(dir)? {
+        total += match entry.size {
+            Some(size) => size,
+            None => sizes_from(
+                &dir.join(&entry.name),
+                format!("{}/{}", path.trim_end_matches('/'), entry.name),
+                result,
+            )?,
+        };
+    }
+    result[index].1 = total;
+    Ok(total)
+}
+
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
@@ -1,106 +1,132 @@
 use crate::parser::*;
 use std::io;
+use std::io::Write;
+use std::str::FromStr;
 
 const INPUT_FILE: &str = "input/input08.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input08_sample.txt";
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Heatmap {
+    Pgm,
+    Ppm,
+    Csv,
+}
+
 #[derive(Debug)]
 struct Input {
     grid: Vec<Vec<u8>>,
 }
 
+#[derive(Debug)]
+struct Views {
+    visible: Vec<Vec<bool>>,
+    scenic: Vec<Vec<u64>>,
+}
+
 impl Input {
-    fn ranges(&self) -> (usize, usize, usize) {
-        let (row_count, column_count) = (self.grid.len(), self.grid[0].len());
-        (row_count, column_count, row_count * column_count)
-    }
-
-    fn count_visible(&self) -> u32 {
-        let (row_count, column_count, tree_count) = self.ranges();
-        let mut visible_count = 0;
-        for tree in 0..tree_count {
-            let (x, y) = (tree / row_count, tree % row_count);
-            let height = &self.grid[x][y];
-            let mut hidden_left = false;
-            let mut hidden_right = false;
-            let mut hidden_up = false;
-            let mut hidden_down = false;
-            for i in 0..x {
-                if self.grid[i][y] >= *height {
-                    hidden_left = true;
-                    break;
-                }
-            }
-            for i in (x + 1)..column_count {
-                if self.grid[i][y] >= *height {
-                    hidden_right = true;
-                    break;
-                }
-            }
-            for i in 0..y {
-                if self.grid[x][i] >= *height {
-                    hidden_up = true;
-                    break;
-                }
-            }
-            for i in (y + 1)..row_count {
-                if self.grid[x][i] >= *height {
-                    hidden_down = true;
-                    break;
-                }
-            }
-            if !(hidden_left && hidden_right && hidden_up && hidden_down) {
-                visible_count += 1;
-            }
+    fn sight_lines(&self) -> Vec<Vec<(usize, usize)>> {
+        let (rows, columns) = (self.grid.len(), self.grid[0].len());
+        let mut lines = vec![];
+        for x in 0..rows {
+            lines.push((0..columns).map(|y| (x, y)).collect::<Vec<_>>());
+            lines.push((0..columns).rev().map(|y| (x, y)).collect());
         }
-        visible_count
+        for y in 0..columns {
+            lines.push((0..rows).map(|x| (x, y)).collect::<Vec<_>>());
+            lines.push((0..rows).rev().map(|x| (x, y)).collect());
+        }
+        lines
     }
 
-    fn find_highest_scenic_score(&self) -> u32 {
-        let (row_count, _, tree_count) = self.ranges();
-        let mut highest_score = 0;
-        for tree in 0..tree_count {
-            let (x, y) = (tree / row_count, tree % row_count);
-            let score = self.scenic_score(x, y);
-            if score > highest_score {
-                highest_score = score;
+    fn views(&self) -> Views {
+        let (rows, columns) = (self.grid.len(), self.grid[0].len());
+        let mut views = Views {
+            visible: vec![vec![false; columns]; rows],
+            scenic: vec![vec![1; columns]; rows],
+        };
+        for line in self.sight_lines() {
+            let mut blockers: Vec<(u8, usize)> = vec![];
+            for (i, &(x, y)) in line.iter().enumerate() {
+                let height = self.grid[x][y];
+                while matches!(blockers.last(), Some(&(h, _)) if h < height) {
+                    blockers.pop();
+                }
+                let distance = match blockers.last() {
+                    Some(&(_, j)) => i - j,
+                    None => {
+                        views.visible[x][y] = true;
+                        i
+                    }
+                };
+                views.scenic[x][y] *= distance as u64;
+                blockers.push((height, i));
             }
         }
-        highest_score
-    }
-
-    fn scenic_score(&self, x: usize, y: usize) -> u32 {
-        let (row_count, column_count, _) = self.ranges();
-        let height = &self.grid[x][y];
-        let mut left_score = 0;
-        let mut right_score = 0;
-        let mut up_score = 0;
-        let mut down_score = 0;
-        for i in 0..x {
-            left_score += 1;
-            if height <= &self.grid[x - 1 - i][y] {
-                break;
+        views
+    }
+
+    fn count_visible(&self) -> usize {
+        self.views()
+            .visible
+            .iter()
+            .flatten()
+            .filter(|v| **v)
+            .count()
+    }
+
+    fn find_highest_scenic_score(&self) -> u64 {
+        self.views()
+            .scenic
+            .iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+fn write_heatmap(scores: &[Vec<u64>], format: Heatmap, out: &mut impl Write) -> io::Result<()> {
+    let (rows, columns) = (scores.len(), scores.first().map_or(0, |r| r.len()));
+    let max = scores.iter().flatten().copied().max().unwrap_or(0).max(1);
+    let level = |score: u64| (score as u128 * 255 / max as u128) as u8;
+    match format {
+        Heatmap::Csv => {
+            for row in scores {
+                let row: Vec<String> = row.iter().map(|s| s.to_string()).collect();
+                writeln!(out, "{}", row.join(","))?;
             }
         }
-        for i in (x + 1)..column_count {
-            right_score += 1;
-            if height <= &self.grid[i][y] {
-                break;
-            }
+        Heatmap::Pgm => {
+            write!(out, "P5\n{} {}\n255\n", columns, rows)?;
+            let pixels: Vec<u8> = scores.iter().flatten().map(|s| level(*s)).collect();
+            out.write_all(&pixels)?;
         }
-        for i in 0..y {
-            up_score += 1;
-            if height <= &self.grid[x][y - 1 - i] {
-                break;
+        Heatmap::Ppm => {
+            write!(out, "P6\n{} {}\n255\n", columns, rows)?;
+            let mut pixels = Vec::with_capacity(rows * columns * 3);
+            for score in scores.iter().flatten() {
+                let t = u32::from(level(*score)) * 3;
+                pixels.push(t.min(255) as u8);
+                pixels.push(t.saturating_sub(255).min(255) as u8);
+                pixels.push(t.saturating_sub(510) as u8);
             }
+            out.write_all(&pixels)?;
         }
-        for i in (y + 1)..row_count {
-            down_score += 1;
-            if height <= &self.grid[x][i] {
-                break;
-            }
+    }
+    Ok(())
+}
+
+impl FromStr for Heatmap {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pgm" => Ok(Heatmap::Pgm),
+            "ppm" => Ok(Heatmap::Ppm),
+            "csv" => Ok(Heatmap::Csv),
+            _ => error(&format!("Unknown heatmap format: {}", s)),
         }
-        left_score * right_score * up_score * down_score
     }
 }
 
@@ -112,37 +138,150 @@ impl TryFrom<&mut FileLines> for Input {
         for line in lines {
             let mut row: Vec<u8> = Vec::new();
             for b in line.as_bytes() {
+                if !b.is_ascii_digit() {
+                    return error(&format!("Invalid tree height: {}", *b as char));
+                }
                 row.push(*b - b'0');
             }
+            if grid
+                .first()
+                .map_or(row.is_empty(), |r| r.len() != row.len())
+            {
+                return error(&format!("Row {} has {} trees", grid.len(), row.len()));
+            }
             grid.push(row);
         }
+        if grid.is_empty() {
+            return error("Forest has no trees");
+        }
         Ok(Input { grid: grid })
     }
 }
 
-pub fn part_1() -> io::Result<u32> {
+pub fn part_1() -> io::Result<usize> {
     let input = Input::try_from(&mut FileLines::new(INPUT_FILE)?)?;
     Ok(input.count_visible())
 }
 
-fn _part_1_sample() -> io::Result<u32> {
+pub fn _part_1_sample() -> io::Result<usize> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(input.count_visible())
 }
 
-pub fn part_2() -> io::Result<u32> {
+pub fn part_2() -> io::Result<u64> {
     let input = Input::try_from(&mut FileLines::new(INPUT_FILE)?)?;
     Ok(input.find_highest_scenic_score())
 }
 
-fn _part_2_sample() -> io::Result<u32> {
+pub fn _part_2_sample() -> io::Result<u64> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(input.find_highest_scenic_score())
 }
 
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["heatmap", format, rest @ ..] => {
+            let format = Heatmap::from_str(format)?;
+            let input = Input::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?)?;
+            write_heatmap(&input.views().scenic, format, &mut io::stdout().lock())?;
+        }
+        ["best", rest @ ..] => {
+            let input = Input::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?)?;
+            let views = input.views();
+            let best = input.find_highest_scenic_score();
+            for (x, row) in views.scenic.iter().enumerate() {
+                for (y, score) in row.iter().enumerate() {
+                    if *score == best {
+                        println!("{},{}\t{}", x, y, score);
+                    }
+                }
+            }
+        }
+        _ => error("Usage: 8 (heatmap (pgm|ppm|csv)|best) [file]")?,
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    use super::{
+        _part_1_sample, _part_2_sample, part_1, part_2, write_heatmap, FileLines, Heatmap, Input,
+        _INPUT_FILE_SAMPLE,
+    };
+
+    fn sample() -> Input {
+        Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE).unwrap()).unwrap()
+    }
+
+    fn naive_scenic_score(grid: &[Vec<u8>], x: usize, y: usize) -> u64 {
+        let (rows, columns) = (grid.len() as i64, grid[0].len() as i64);
+        let mut score = 1;
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (mut i, mut j, mut distance) = (x as i64 + dx, y as i64 + dy, 0);
+            while i >= 0 && j >= 0 && i < rows && j < columns {
+                distance += 1;
+                if grid[i as usize][j as usize] >= grid[x][y] {
+                    break;
+                }
+                i += dx;
+                j += dy;
+            }
+            score *= distance;
+        }
+        score
+    }
+
+    #[test]
+    fn test_views() {
+        let views = sample().views();
+        assert_eq!(4, views.scenic[1][2]);
+        assert_eq!(8, views.scenic[3][2]);
+        assert!(views.visible[1][1]);
+        assert!(!views.visible[1][3]);
+        assert!(!views.visible[2][2]);
+
+        let mut seed: u64 = 7;
+        let grid: Vec<Vec<u8>> = (0..40)
+            .map(|_| {
+                (0..30)
+                    .map(|_| {
+                        seed = seed
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        (seed >> 60) as u8 % 10
+                    })
+                    .collect()
+            })
+            .collect();
+        let input = Input { grid };
+        let views = input.views();
+        for x in 0..40 {
+            for y in 0..30 {
+                assert_eq!(naive_scenic_score(&input.grid, x, y), views.scenic[x][y]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let scores = sample().views().scenic;
+        let mut csv = vec![];
+        write_heatmap(&scores, Heatmap::Csv, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(Some("0,1,4,1,0"), csv.lines().nth(1));
+
+        let mut pgm = vec![];
+        write_heatmap(&scores, Heatmap::Pgm, &mut pgm).unwrap();
+        assert!(pgm.starts_with(b"P5\n5 5\n255\n"));
+        assert_eq!(11 + 25, pgm.len());
+        assert_eq!(255, pgm[11 + 3 * 5 + 2]);
+
+        let mut ppm = vec![];
+        write_heatmap(&scores, Heatmap::Ppm, &mut ppm).unwrap();
+        assert_eq!(11 + 75, ppm.len());
+        assert_eq!([255, 255, 255], ppm[11 + 3 * 17..11 + 3 * 18]);
+        assert_eq!([0, 0, 0], ppm[11..14]);
+    }
 
     #[test]
     fn test_part_1() {
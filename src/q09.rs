@@ -1,33 +1,57 @@
 use crate::parser::*;
 use std::collections::HashSet;
+use std::fs::File;
 use std::io;
+use std::io::Write;
+use std::path::Path;
 
 const INPUT_FILE: &str = "input/input09.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input09_sample.txt";
 const _INPUT_FILE_SAMPLE_2: &str = "input/input09_sample_2.txt";
 
-enum Direction {
-    LEFT,
-    RIGHT,
-    UP,
-    DOWN,
+type Point = [i32; 3];
+
+#[derive(Debug, PartialEq)]
+struct Move {
+    delta: Point,
+    distance: u32,
 }
 
-struct Move(Direction, u32);
+impl Move {
+    fn is_diagonal(&self) -> bool {
+        self.delta.iter().filter(|d| **d != 0).count() > 1
+    }
+}
 
 impl TryFrom<&mut FileLines> for Move {
     type Error = io::Error;
 
     fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
-        match lines.next_result()?.split_once(' ') {
-            Some((direction, distance)) => match direction {
-                "L" => Ok(Move(Direction::LEFT, distance.parse().unwrap())),
-                "R" => Ok(Move(Direction::RIGHT, distance.parse().unwrap())),
-                "U" => Ok(Move(Direction::UP, distance.parse().unwrap())),
-                "D" => Ok(Move(Direction::DOWN, distance.parse().unwrap())),
-                _ => error("Failed to parse direction"),
-            },
-            None => error("Failed to match line with direction and distance"),
+        let line = lines.next_result()?;
+        match line.split_once(' ') {
+            Some((direction, distance)) => {
+                let mut delta = [0; 3];
+                for c in direction.chars() {
+                    let (axis, step) = match c {
+                        'L' => (0, -1),
+                        'R' => (0, 1),
+                        'U' => (1, 1),
+                        'D' => (1, -1),
+                        'F' => (2, 1),
+                        'B' => (2, -1),
+                        _ => return error(&format!("Failed to parse direction: {}", direction)),
+                    };
+                    if delta[axis] != 0 {
+                        return error(&format!("Failed to parse direction: {}", direction));
+                    }
+                    delta[axis] = step;
+                }
+                Ok(Move {
+                    delta,
+                    distance: arg(distance)?,
+                })
+            }
+            None => error(&format!("Failed to match direction and distance: {}", line)),
         }
     }
 }
@@ -40,99 +64,281 @@ impl Iterator for FileInput<FileLines, Move> {
     }
 }
 
+#[derive(Debug)]
+struct Rope {
+    knots: Vec<Point>,
+    max_stretch: u32,
+    diagonal_moves: bool,
+    visited: Vec<HashSet<Point>>,
+}
+
+impl Rope {
+    fn new(knot_count: usize) -> Self {
+        let knot_count = knot_count.max(1);
+        Rope {
+            knots: vec![[0; 3]; knot_count],
+            max_stretch: 1,
+            diagonal_moves: false,
+            visited: vec![HashSet::from([[0; 3]]); knot_count],
+        }
+    }
+
+    fn with_max_stretch(mut self, max_stretch: u32) -> Self {
+        self.max_stretch = max_stretch.max(1);
+        self
+    }
+
+    fn with_diagonal_moves(mut self) -> Self {
+        self.diagonal_moves = true;
+        self
+    }
+
+    fn apply(&mut self, moov: &Move) -> io::Result<()> {
+        if moov.is_diagonal() && !self.diagonal_moves {
+            return error(&format!("Diagonal head moves are disabled: {:?}", moov));
+        }
+        for _ in 0..moov.distance {
+            self.step(moov.delta);
+        }
+        Ok(())
+    }
+
+    fn step(&mut self, delta: Point) {
+        for (position, d) in self.knots[0].iter_mut().zip(delta) {
+            *position += d;
+        }
+        self.visited[0].insert(self.knots[0]);
+        for i in 1..self.knots.len() {
+            let head = self.knots[i - 1];
+            let tail = &mut self.knots[i];
+            let diff: Point = std::array::from_fn(|axis| head[axis] - tail[axis]);
+            if diff.iter().map(|d| d.unsigned_abs()).max().unwrap() > self.max_stretch {
+                for (position, d) in tail.iter_mut().zip(diff) {
+                    *position += d.signum();
+                }
+                self.visited[i].insert(*tail);
+            }
+        }
+    }
+
+    fn tail(&self) -> usize {
+        self.knots.len() - 1
+    }
+
+    fn visited_by_any(&self) -> usize {
+        let all: HashSet<&Point> = self.visited.iter().flatten().collect();
+        all.len()
+    }
+
+    fn bounds(&self) -> ((i32, i32), (i32, i32)) {
+        let points = self.visited.iter().flatten();
+        let min_x = points.clone().map(|p| p[0]).min().unwrap();
+        let max_x = points.clone().map(|p| p[0]).max().unwrap();
+        let min_y = points.clone().map(|p| p[1]).min().unwrap();
+        let max_y = points.map(|p| p[1]).max().unwrap();
+        ((min_x, min_y), (max_x, max_y))
+    }
+
+    fn symbol(&self, (x, y): (i32, i32), trail: &HashSet<(i32, i32)>) -> char {
+        match self.knots.iter().position(|k| k[0] == x && k[1] == y) {
+            Some(0) => 'H',
+            Some(i) if i == self.tail() && self.knots.len() == 2 => 'T',
+            Some(i) if i < 10 => char::from_digit(i as u32, 10).unwrap(),
+            Some(_) => '*',
+            None if (x, y) == (0, 0) => 's',
+            None if trail.contains(&(x, y)) => '#',
+            None => '.',
+        }
+    }
+
+    fn projected_trail(&self, trail: usize) -> HashSet<(i32, i32)> {
+        self.visited[trail].iter().map(|p| (p[0], p[1])).collect()
+    }
+
+    fn render(&self, trail: usize) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds();
+        let trail = self.projected_trail(trail);
+        let mut output = String::new();
+        for y in (min_y..=max_y).rev() {
+            for x in min_x..=max_x {
+                output.push(self.symbol((x, y), &trail));
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    fn write_ppm(&self, trail: usize, out: &mut impl Write) -> io::Result<()> {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds();
+        let trail = self.projected_trail(trail);
+        write!(
+            out,
+            "P6\n{} {}\n255\n",
+            max_x - min_x + 1,
+            max_y - min_y + 1
+        )?;
+        let mut pixels = vec![];
+        for y in (min_y..=max_y).rev() {
+            for x in min_x..=max_x {
+                pixels.extend(match self.symbol((x, y), &trail) {
+                    'H' => [255, 0, 0],
+                    '.' => [0, 0, 0],
+                    '#' => [96, 96, 96],
+                    's' => [0, 0, 255],
+                    _ => [255, 255, 0],
+                });
+            }
+        }
+        out.write_all(&pixels)
+    }
+}
+
+fn simulate(input_file: &str, mut rope: Rope) -> io::Result<Rope> {
+    for moov in parse_lines::<Move>(input_file)? {
+        rope.apply(&moov)?;
+    }
+    Ok(rope)
+}
+
 pub fn part_1() -> io::Result<usize> {
     do_part_1(INPUT_FILE)
 }
 
-fn _part_1_sample() -> io::Result<usize> {
+pub fn _part_1_sample() -> io::Result<usize> {
     do_part_1(_INPUT_FILE_SAMPLE)
 }
 
 fn do_part_1(input_file: &str) -> io::Result<usize> {
-    let moves = parse_lines::<Move>(input_file)?;
-    let mut visited: HashSet<(i32, i32)> = HashSet::new();
-    let mut head: (i32, i32) = (0, 0);
-    let mut tail: (i32, i32) = (0, 0);
-    for moov in moves {
-        let direction = moov.0;
-        for _ in 0..moov.1 {
-            match direction {
-                Direction::LEFT => head.0 -= 1,
-                Direction::RIGHT => head.0 += 1,
-                Direction::UP => head.1 += 1,
-                Direction::DOWN => head.1 -= 1,
-            }
-            follow_knot(&head, &mut tail);
-            visited.insert(tail);
-        }
-    }
-    Ok(visited.len())
+    let rope = simulate(input_file, Rope::new(2))?;
+    Ok(rope.visited[rope.tail()].len())
 }
 
 pub fn part_2() -> io::Result<usize> {
     do_part_2(INPUT_FILE)
 }
 
-fn _part_2_sample() -> io::Result<usize> {
+pub fn _part_2_sample() -> io::Result<usize> {
     do_part_2(_INPUT_FILE_SAMPLE)
 }
 
-fn _part_2_sample_2() -> io::Result<usize> {
+pub fn _part_2_sample_2() -> io::Result<usize> {
     do_part_2(_INPUT_FILE_SAMPLE_2)
 }
 
 fn do_part_2(input_file: &str) -> io::Result<usize> {
-    let mut visited: HashSet<(i32, i32)> = HashSet::new();
-    let mut knots: Vec<(i32, i32)> = vec![(0, 0); 10];
-    let moves = parse_lines::<Move>(input_file)?;
-    for moov in moves {
-        let direction = moov.0;
-        for _ in 0..moov.1 {
-            match direction {
-                Direction::LEFT => knots[0].0 -= 1,
-                Direction::RIGHT => knots[0].0 += 1,
-                Direction::UP => knots[0].1 += 1,
-                Direction::DOWN => knots[0].1 -= 1,
-            }
-            for i in 1..knots.len() {
-                let head = knots[i - 1];
-                let tail = &mut knots[i];
-                follow_knot(&head, tail);
-            }
-            visited.insert(knots[9]);
+    let rope = simulate(input_file, Rope::new(10))?;
+    Ok(rope.visited[rope.tail()].len())
+}
+
+fn rope_arg(args: &[&str]) -> io::Result<Rope> {
+    let mut rope = Rope::new(arg(args[0])?);
+    for option in &args[1..] {
+        match option.split_once('=') {
+            Some(("stretch", s)) => rope = rope.with_max_stretch(arg(s)?),
+            None if *option == "diagonal" => rope = rope.with_diagonal_moves(),
+            _ => error(&format!("Unknown rope option: {}", option))?,
         }
     }
-    Ok(visited.len())
+    Ok(rope)
 }
 
-fn follow_knot(head: &(i32, i32), tail: &mut (i32, i32)) {
-    let diff_x = tail.0 - head.0;
-    let diff_y = tail.1 - head.1;
-
-    if diff_x.abs() == 2 {
-        tail.0 -= diff_x / 2;
-        if diff_y.abs() == 1 {
-            tail.1 = head.1;
-        } else if diff_y == 2 {
-            tail.1 -= 1;
-        } else if diff_y == -2 {
-            tail.1 += 1;
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    let (rope_args, file_args) = match args.iter().position(|a| *a == "--") {
+        Some(i) => (&args[..i], &args[i + 1..]),
+        None => (args, &[][..]),
+    };
+    match rope_args {
+        ["visited", rope @ ..] if !rope.is_empty() => {
+            let rope = simulate(file_arg(file_args, INPUT_FILE), rope_arg(rope)?)?;
+            for (i, visited) in rope.visited.iter().enumerate() {
+                println!("{}\t{}", i, visited.len());
+            }
+            println!("any\t{}", rope.visited_by_any());
         }
-    } else if diff_y.abs() == 2 {
-        tail.1 -= diff_y / 2;
-        if diff_x.abs() == 1 {
-            tail.0 = head.0;
-        } else if diff_x == 2 {
-            tail.0 -= 1;
-        } else if diff_x == -2 {
-            tail.0 += 1;
+        ["frames", rope @ ..] if !rope.is_empty() => {
+            let mut rope = rope_arg(rope)?;
+            for moov in parse_lines::<Move>(file_arg(file_args, INPUT_FILE))? {
+                rope.apply(&moov)?;
+                println!("== {:?} ==\n{}", moov, rope.render(rope.tail()));
+            }
+        }
+        ["images", dir, rope @ ..] if !rope.is_empty() => {
+            let mut rope = rope_arg(rope)?;
+            let moves = parse_lines::<Move>(file_arg(file_args, INPUT_FILE))?;
+            for (i, moov) in moves.enumerate() {
+                rope.apply(&moov)?;
+                let mut file = File::create(Path::new(dir).join(format!("frame{:05}.ppm", i)))?;
+                rope.write_ppm(rope.tail(), &mut file)?;
+            }
         }
+        _ => error(
+            "Usage: 9 (visited|frames|images <dir>) <knots> [stretch=<n>] [diagonal] [-- file]",
+        )?,
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, _part_2_sample_2, part_1, part_2};
+    use super::{
+        _part_1_sample, _part_2_sample, _part_2_sample_2, part_1, part_2, simulate, Move, Rope,
+        _INPUT_FILE_SAMPLE,
+    };
+
+    #[test]
+    fn test_render() {
+        let rope = simulate(_INPUT_FILE_SAMPLE, Rope::new(2)).unwrap();
+        assert_eq!(
+            "..##..\n...##.\n.TH##.\n....#.\ns###..\n",
+            rope.render(rope.tail())
+        );
+        assert_eq!(
+            simulate(_INPUT_FILE_SAMPLE, Rope::new(10)).unwrap().visited[1].len(),
+            rope.visited[1].len()
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        let diagonal = Move {
+            delta: [1, 1, 0],
+            distance: 3,
+        };
+        assert!(Rope::new(3).apply(&diagonal).is_err());
+        let mut rope = Rope::new(3).with_diagonal_moves();
+        rope.apply(&diagonal).unwrap();
+        assert_eq!(vec![[3, 3, 0], [2, 2, 0], [1, 1, 0]], rope.knots);
+
+        let mut rope = Rope::new(3).with_max_stretch(2);
+        rope.apply(&Move {
+            delta: [1, 0, 0],
+            distance: 6,
+        })
+        .unwrap();
+        assert_eq!(vec![[6, 0, 0], [4, 0, 0], [2, 0, 0]], rope.knots);
+        assert_eq!(7, rope.visited[0].len());
+        assert_eq!(3, rope.visited[2].len());
+    }
+
+    #[test]
+    fn test_3d() {
+        let mut rope = Rope::new(2);
+        for delta in [[1, 0, 0], [0, 0, 1]] {
+            rope.apply(&Move { delta, distance: 2 }).unwrap();
+        }
+        assert_eq!(vec![[2, 0, 2], [2, 0, 1]], rope.knots);
+        assert_eq!(5, rope.visited_by_any());
+        assert_eq!("s#H\n", rope.render(1));
+
+        let mut rope = Rope::new(3).with_diagonal_moves();
+        rope.apply(&Move {
+            delta: [1, -1, 1],
+            distance: 4,
+        })
+        .unwrap();
+        assert_eq!(vec![[4, -4, 4], [3, -3, 3], [2, -2, 2]], rope.knots);
+        assert_eq!(5, rope.visited_by_any());
+    }
 
     #[test]
     fn test_part_1() {
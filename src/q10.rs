@@ -1,142 +1,184 @@
+mod assembler;
+mod cpu;
+mod debugger;
+mod ocr;
+
 use crate::parser::*;
+use assembler::*;
+use cpu::*;
+use debugger::*;
+use ocr::decode;
+use std::fs;
 use std::io;
+use std::io::BufRead;
+use std::str::FromStr;
 
 const INPUT_FILE: &str = "input/input10.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input10_sample.txt";
 
-struct Clock(u16, i32);
-
-impl Clock {
-    fn tick(&mut self, x: i32) {
-        self.0 += 1;
-        if (self.0 + 20) % 40 == 0 && self.0 <= 220 {
-            self.1 += i32::from(self.0) * x
+fn load(input_file: &str, instructions: &InstructionSet) -> io::Result<Cpu> {
+    let mut program = vec![];
+    for (i, line) in FileLines::new(input_file)?.enumerate() {
+        match instructions.parse(&line) {
+            Ok(instruction) => program.push(instruction),
+            Err(e) => error(&format!("Line {}: {}", i + 1, e))?,
         }
     }
+    Ok(Cpu::new(program))
 }
 
-enum Input {
-    AddX(i32),
-    Noop,
-}
-
-impl TryFrom<&mut FileLines> for Input {
-    type Error = io::Error;
-
-    fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
-        let line = lines.next_result()?;
-        let mut instruction = line.split(" ");
-        match instruction.next() {
-            Some("addx") => match instruction.next() {
-                Some(i) => Ok(Input::AddX(i.parse().unwrap())),
-                None => error("Failed to parse addx operand"),
-            },
-            Some("noop") => Ok(Input::Noop),
-            _ => error("Failed to parse instruction"),
-        }
-    }
-}
-
-impl Iterator for FileInput<FileLines, Input> {
-    type Item = Input;
-
-    fn next(&mut self) -> Option<Input> {
-        parse_from(&mut self.source)
-    }
-}
-
-pub fn part_1() -> io::Result<i32> {
+pub fn part_1() -> io::Result<i64> {
     do_part_1(INPUT_FILE)
 }
 
-fn _part_1_sample() -> io::Result<i32> {
+pub fn _part_1_sample() -> io::Result<i64> {
     do_part_1(_INPUT_FILE_SAMPLE)
 }
 
-fn do_part_1(input_file: &str) -> io::Result<i32> {
-    let mut instructions = parse_lines::<Input>(input_file)?;
-    let mut x = 1;
-    let mut clock = Clock(0, 0);
-    let mut current_instruction = instructions.next();
-    while clock.0 < 250 {
-        let c = &current_instruction;
-        match c {
-            Some(Input::AddX(a)) => {
-                clock.tick(x);
-                clock.tick(x);
-                x += a;
-            }
-            Some(Input::Noop) => clock.tick(x),
-            None => break,
-        }
-        current_instruction = instructions.next();
-    }
-    Ok(clock.1)
+fn do_part_1(input_file: &str) -> io::Result<i64> {
+    let mut signal = SignalStrength::new(20, 40, 220);
+    load(input_file, &InstructionSet::standard())?.run(&mut [&mut signal]);
+    Ok(signal.total)
 }
 
 pub fn part_2() -> io::Result<String> {
-    do_part_2(INPUT_FILE)
+    decode(&do_part_2(INPUT_FILE)?)
 }
 
-fn _part_2_sample() -> io::Result<String> {
+pub fn _part_2_sample() -> io::Result<String> {
     do_part_2(_INPUT_FILE_SAMPLE)
 }
 
 fn do_part_2(input_file: &str) -> io::Result<String> {
-    let mut instructions = parse_lines::<Input>(input_file)?;
-    let mut x = 1;
-    let mut clock = Clock(0, 0);
-    let mut current_instruction = instructions.next();
-    let mut output = String::from('\n');
-    while clock.0 < 240 {
-        let c = &current_instruction;
-        match c {
-            Some(Input::AddX(a)) => {
-                clock.tick(x);
-                draw_pixel(i32::from(clock.0), x, &mut output);
-                clock.tick(x);
-                draw_pixel(i32::from(clock.0), x, &mut output);
-                x += a;
-            }
-            Some(Input::Noop) => {
-                clock.tick(x);
-                draw_pixel(i32::from(clock.0), x, &mut output);
+    let mut crt = Crt::new(40, 6);
+    load(input_file, &InstructionSet::standard())?.run(&mut [&mut crt]);
+    Ok(String::from('\n') + &crt.render())
+}
+
+fn debug(input_file: &str, breakpoints: &[&str]) -> io::Result<()> {
+    let mut debugger = Debugger::new(load(input_file, &InstructionSet::standard())?);
+    for breakpoint in breakpoints {
+        debugger.breakpoints.push(Breakpoint::from_str(breakpoint)?);
+    }
+    let mut crt = Crt::new(40, 6);
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["step"] | ["s"] => {
+                debugger.step(&mut [&mut crt]);
             }
-            None => break,
+            ["step", n] | ["s", n] => match arg(n) {
+                Ok(n) => {
+                    for _ in 0..n {
+                        debugger.step(&mut [&mut crt]);
+                    }
+                }
+                Err(e) => println!("{}", e),
+            },
+            ["continue"] | ["c"] => match debugger.resume(&mut [&mut crt]) {
+                Some(breakpoint) => println!("Hit {:?}", breakpoint),
+                None => println!("Halted"),
+            },
+            ["break", breakpoint] | ["b", breakpoint] => match Breakpoint::from_str(breakpoint) {
+                Ok(breakpoint) => debugger.breakpoints.push(breakpoint),
+                Err(e) => println!("{}", e),
+            },
+            ["crt"] => print!("{}", crt.render()),
+            ["trace"] => debugger.write_trace(&mut io::stdout())?,
+            ["quit"] | ["q"] => break,
+            _ => println!("Commands: step [n], continue, break <cycle=n|x=v>, crt, trace, quit"),
         }
-        current_instruction = instructions.next();
+        println!(
+            "cycle={} {:?} next={:?}",
+            debugger.cpu.cycle,
+            debugger.cpu.registers,
+            debugger.cpu.current()
+        );
     }
-    Ok(output)
+    Ok(())
 }
 
-fn draw_pixel(current_cycle: i32, sprite: i32, output: &mut String) {
-    let position = (current_cycle - 1) % 40;
-    if sprite - 1 <= position && position <= sprite + 1 {
-        output.push('#');
-    } else {
-        output.push('.');
-    }
-    if position == 39 {
-        output.push('\n');
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["run", rest @ ..] => {
+            let mut signal = SignalStrength::new(20, 40, 220);
+            let mut crt = Crt::new(40, 6);
+            let mut cpu = load(file_arg(rest, INPUT_FILE), &InstructionSet::standard())?;
+            cpu.run(&mut [&mut signal, &mut crt]);
+            println!("Cycles: {}\nSignal strength: {}", cpu.cycle, signal.total);
+            print!("{}", crt.render());
+            match decode(&crt.render()) {
+                Ok(letters) => println!("Letters: {}", letters),
+                Err(e) => println!("{}", e),
+            }
+        }
+        ["debug", rest @ ..] => match rest.iter().position(|a| *a == "--") {
+            Some(i) => debug(file_arg(&rest[i + 1..], INPUT_FILE), &rest[..i])?,
+            None => debug(INPUT_FILE, rest)?,
+        },
+        ["assemble", "text", text] => {
+            for line in assemble(&letters_image(text, 40)?, 40)? {
+                println!("{}", line);
+            }
+        }
+        ["assemble", "image", file] => {
+            let image = fs::read_to_string(file)?;
+            let width = image.lines().find(|l| !l.is_empty()).map_or(0, |l| l.len());
+            for line in assemble(&image, width)? {
+                println!("{}", line);
+            }
+        }
+        _ => error(
+            "Usage: 10 (run [file]|debug [breakpoint ...] [-- file]\
+             |assemble (text <letters>|image <file>))",
+        )?,
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    use super::{
+        _part_1_sample, _part_2_sample, assemble, decode, letters_image, load, part_1, part_2,
+        Breakpoint, Crt, Debugger, Instruction, InstructionSet, Registers, SignalStrength,
+        _INPUT_FILE_SAMPLE,
+    };
 
-    #[test]
-    fn test_part_1() {
-        assert_eq!(14540, part_1().unwrap());
+    #[derive(Debug)]
+    struct Double;
+
+    impl Instruction for Double {
+        fn cycles(&self) -> u32 {
+            3
+        }
+
+        fn execute(&self, registers: &mut Registers) {
+            *registers.get_mut(&'x').unwrap() *= 2;
+        }
     }
 
     #[test]
-    fn test_part_1_sample() {
-        assert_eq!(13140, _part_1_sample().unwrap());
+    fn test_custom_instruction() {
+        let mut instructions = InstructionSet::standard();
+        instructions.register("dblx", |_| Ok(Box::new(Double)));
+        let program = vec![
+            instructions.parse("addx 2").unwrap(),
+            instructions.parse("dblx").unwrap(),
+            instructions.parse("noop").unwrap(),
+        ];
+        let mut signal = SignalStrength::new(1, 1, 10);
+        let mut cpu = super::Cpu::new(program);
+        cpu.run(&mut [&mut signal]);
+        assert_eq!(6, cpu.cycle);
+        assert_eq!(Some(&6), cpu.registers.get(&'x'));
+        assert_eq!(1 + 2 + 3 * 3 + 4 * 3 + 5 * 3 + 6 * 6, signal.total);
+        assert!(instructions.parse("mulx 2").is_err());
+        assert!(instructions.parse("addx").is_err());
     }
 
     #[test]
-    fn test_part_2() {
+    fn test_decode() {
         let mut s = String::from('\n');
         s += "####.#..#.####.####.####.#..#..##..####.\n";
         s += "#....#..#....#.#.......#.#..#.#..#....#.\n";
@@ -144,7 +186,90 @@ mod tests {
         s += "#....#..#..#...#.....#...#..#.#.....#...\n";
         s += "#....#..#.#....#....#....#..#.#..#.#....\n";
         s += "####.#..#.####.#....####.#..#..##..####.\n";
-        assert_eq!(s, part_2().unwrap());
+        assert_eq!("EHZFZHCZ", decode(&s).unwrap());
+
+        let e = decode(&_part_2_sample().unwrap()).unwrap_err().to_string();
+        assert_eq!(
+            "Unrecognised glyph at column 0:\n##..\n###.\n####\n####\n####\n####",
+            e
+        );
+        assert!(decode("####\n").is_err());
+        let blocks: String = s
+            .lines()
+            .skip(1)
+            .map(|l| format!("{}█{}\n", &l[..3], &l[4..]))
+            .collect();
+        let e = decode(&blocks).unwrap_err().to_string();
+        assert!(e.contains("non-ASCII"), "{}", e);
+    }
+
+    fn draw(program: &[String]) -> String {
+        let instructions = InstructionSet::standard();
+        let program = program
+            .iter()
+            .map(|l| instructions.parse(l).unwrap())
+            .collect();
+        let mut crt = Crt::new(40, 6);
+        super::Cpu::new(program).run(&mut [&mut crt]);
+        crt.render()
+    }
+
+    #[test]
+    fn test_assemble() {
+        for text in ["EHZFZHCZ", "BFPRZ", "ZAGHOJKL", "E"] {
+            let image = letters_image(text, 40).unwrap();
+            let program = assemble(&image, 40).unwrap();
+            assert_eq!(image, draw(&program));
+            assert_eq!(text, decode(&draw(&program)).unwrap());
+        }
+        let sample = _part_2_sample().unwrap();
+        assert_eq!(
+            sample,
+            String::from('\n') + &draw(&assemble(&sample, 40).unwrap())
+        );
+
+        assert!(assemble(&letters_image("HELLO", 40).unwrap(), 40).is_err());
+        assert!(letters_image("EEEEEEEEE", 40).is_err());
+        assert!(letters_image("E!", 40).is_err());
+    }
+
+    #[test]
+    fn test_debugger() {
+        let cpu = load(_INPUT_FILE_SAMPLE, &InstructionSet::standard()).unwrap();
+        let mut debugger = Debugger::new(cpu);
+        debugger.breakpoints.push(Breakpoint::Cycle(20));
+        debugger.breakpoints.push("x=1".parse().unwrap());
+        assert_eq!(Some(Breakpoint::Cycle(20)), debugger.resume(&mut []));
+        assert_eq!(19, debugger.cpu.cycle);
+        assert_eq!(Some(&21), debugger.cpu.registers.get(&'x'));
+        assert!(debugger.step(&mut []));
+        assert_eq!(Some(Breakpoint::Register('x', 1)), debugger.resume(&mut []));
+        assert_eq!(39, debugger.cpu.cycle);
+        while debugger.resume(&mut []).is_some() {}
+        assert_eq!(240, debugger.trace.len());
+
+        let mut out = vec![];
+        debugger.write_trace(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(Some("cycle,x"), out.lines().next());
+        assert_eq!(Some("1,1"), out.lines().nth(1));
+        assert_eq!(Some("2,16"), out.lines().nth(2));
+        assert!("y=1=2".parse::<Breakpoint>().is_err());
+    }
+
+    #[test]
+    fn test_part_1() {
+        assert_eq!(14540, part_1().unwrap());
+    }
+
+    #[test]
+    fn test_part_1_sample() {
+        assert_eq!(13140, _part_1_sample().unwrap());
+    }
+
+    #[test]
+    fn test_part_2() {
+        assert_eq!("EHZFZHCZ", part_2().unwrap());
     }
 
     #[test]
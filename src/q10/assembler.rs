@@ -0,0 +1,84 @@
+use super::ocr::*;
+use crate::parser::*;
+use std::io;
+
+const MIN_SPRITE: i64 = -2;
+
+pub fn letters_image(text: &str, width: usize) -> io::Result<String> {
+    if text.chars().count() * GLYPH_SPACING > width + GLYPH_SPACING - GLYPH_WIDTH {
+        return error(&format!("{} does not fit in {} columns", text, width));
+    }
+    let mut rows = vec![String::new(); GLYPH_HEIGHT];
+    for letter in text.chars() {
+        let glyph = match letter {
+            ' ' => ["...."; GLYPH_HEIGHT],
+            _ => match glyph(letter) {
+                Some(glyph) => glyph,
+                None => return error(&format!("No glyph for letter: {}", letter)),
+            },
+        };
+        for (row, line) in rows.iter_mut().zip(glyph) {
+            row.push_str(line);
+            row.push('.');
+        }
+    }
+    let mut image = String::new();
+    for row in rows {
+        image.push_str(&format!("{:.<width$}\n", &row[..row.len().min(width)]));
+    }
+    Ok(image)
+}
+
+pub fn assemble(image: &str, width: usize) -> io::Result<Vec<String>> {
+    let pixels: Vec<bool> = image
+        .lines()
+        .filter(|l| !l.is_empty())
+        .flat_map(|l| l.chars().map(|c| c == '#'))
+        .collect();
+    if width == 0 || !pixels.len().is_multiple_of(width) {
+        return error(&format!("Image is not {} pixels wide", width));
+    }
+    let cycles = pixels.len();
+    let sprites: Vec<i64> = (MIN_SPRITE..=width as i64 + 1).collect();
+    let draws = |cycle: usize, sprite: i64| match pixels.get(cycle) {
+        Some(lit) => *lit == ((sprite - (cycle % width) as i64).abs() <= 1),
+        None => true,
+    };
+
+    // fewest[c][s]: fewest instructions drawing pixels c.. with the sprite at s
+    let mut fewest = vec![vec![Some(0); sprites.len()]; cycles + 2];
+    for cycle in (0..cycles).rev() {
+        let after_addx = fewest[cycle + 2].iter().flatten().min().copied();
+        for (i, sprite) in sprites.iter().enumerate() {
+            fewest[cycle][i] = if !draws(cycle, *sprite) {
+                None
+            } else {
+                let noop = fewest[cycle + 1][i];
+                let addx = after_addx.filter(|_| draws(cycle + 1, *sprite));
+                [noop, addx].into_iter().flatten().min().map(|n| n + 1)
+            };
+        }
+    }
+
+    let mut program = vec![];
+    let (mut cycle, mut i) = (0, (1 - MIN_SPRITE) as usize);
+    while cycle < cycles {
+        let remaining = match fewest[cycle][i] {
+            Some(remaining) => remaining,
+            None => return error(&format!("No program can draw pixel {}", cycle)),
+        };
+        if fewest[cycle + 1][i] == Some(remaining - 1) {
+            program.push(String::from("noop"));
+            cycle += 1;
+        } else {
+            let next = fewest[cycle + 2]
+                .iter()
+                .position(|n| *n == Some(remaining - 1))
+                .unwrap();
+            program.push(format!("addx {}", sprites[next] - sprites[i]));
+            cycle += 2;
+            i = next;
+        }
+    }
+    Ok(program)
+}
@@ -0,0 +1,196 @@
+use crate::parser::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+
+pub type Registers = BTreeMap<char, i64>;
+
+pub trait Instruction: fmt::Debug {
+    fn cycles(&self) -> u32;
+    fn execute(&self, registers: &mut Registers);
+}
+
+#[derive(Debug)]
+pub struct Add {
+    pub register: char,
+    pub value: i64,
+}
+
+impl Instruction for Add {
+    fn cycles(&self) -> u32 {
+        2
+    }
+
+    fn execute(&self, registers: &mut Registers) {
+        *registers.entry(self.register).or_default() += self.value;
+    }
+}
+
+#[derive(Debug)]
+pub struct Noop;
+
+impl Instruction for Noop {
+    fn cycles(&self) -> u32 {
+        1
+    }
+
+    fn execute(&self, _registers: &mut Registers) {}
+}
+
+pub type Parser = fn(&[&str]) -> io::Result<Box<dyn Instruction>>;
+
+pub struct InstructionSet {
+    parsers: HashMap<String, Parser>,
+}
+
+impl InstructionSet {
+    pub fn standard() -> Self {
+        let mut set = InstructionSet {
+            parsers: HashMap::new(),
+        };
+        set.register("addx", |operands| match operands {
+            [value] => Ok(Box::new(Add {
+                register: 'x',
+                value: arg(value)?,
+            })),
+            _ => error("addx takes one operand"),
+        });
+        set.register("noop", |operands| match operands {
+            [] => Ok(Box::new(Noop)),
+            _ => error("noop takes no operands"),
+        });
+        set
+    }
+
+    pub fn register(&mut self, mnemonic: &str, parser: Parser) {
+        self.parsers.insert(String::from(mnemonic), parser);
+    }
+
+    pub fn parse(&self, line: &str) -> io::Result<Box<dyn Instruction>> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.split_first() {
+            Some((mnemonic, operands)) => match self.parsers.get(*mnemonic) {
+                Some(parser) => parser(operands),
+                None => error(&format!("Unknown instruction: {}", mnemonic)),
+            },
+            None => error("Empty instruction"),
+        }
+    }
+}
+
+pub trait Observer {
+    fn observe(&mut self, cycle: u64, registers: &Registers);
+}
+
+#[derive(Debug)]
+pub struct SignalStrength {
+    pub first: u64,
+    pub period: u64,
+    pub last: u64,
+    pub total: i64,
+}
+
+impl SignalStrength {
+    pub fn new(first: u64, period: u64, last: u64) -> Self {
+        SignalStrength {
+            first,
+            period,
+            last,
+            total: 0,
+        }
+    }
+}
+
+impl Observer for SignalStrength {
+    fn observe(&mut self, cycle: u64, registers: &Registers) {
+        if cycle >= self.first
+            && cycle <= self.last
+            && (cycle - self.first).is_multiple_of(self.period)
+        {
+            self.total += cycle as i64 * registers.get(&'x').copied().unwrap_or(0);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Crt {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<bool>,
+}
+
+impl Crt {
+    pub fn new(width: usize, height: usize) -> Self {
+        Crt {
+            width,
+            height,
+            pixels: vec![],
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        for row in self.pixels.chunks(self.width) {
+            output.extend(row.iter().map(|lit| if *lit { '#' } else { '.' }));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+impl Observer for Crt {
+    fn observe(&mut self, cycle: u64, registers: &Registers) {
+        if cycle as usize > self.width * self.height {
+            return;
+        }
+        let position = ((cycle - 1) % self.width as u64) as i64;
+        let sprite = registers.get(&'x').copied().unwrap_or(0);
+        self.pixels.push((sprite - position).abs() <= 1);
+    }
+}
+
+pub struct Cpu {
+    pub registers: Registers,
+    pub cycle: u64,
+    program: Vec<Box<dyn Instruction>>,
+    pc: usize,
+    elapsed: u32,
+}
+
+impl Cpu {
+    pub fn new(program: Vec<Box<dyn Instruction>>) -> Self {
+        Cpu {
+            registers: Registers::from([('x', 1)]),
+            cycle: 0,
+            program,
+            pc: 0,
+            elapsed: 0,
+        }
+    }
+
+    pub fn current(&self) -> Option<&dyn Instruction> {
+        self.program.get(self.pc).map(|i| i.as_ref())
+    }
+
+    pub fn tick(&mut self, observers: &mut [&mut dyn Observer]) -> bool {
+        let instruction = match self.program.get(self.pc) {
+            Some(instruction) => instruction,
+            None => return false,
+        };
+        self.cycle += 1;
+        for observer in observers.iter_mut() {
+            observer.observe(self.cycle, &self.registers);
+        }
+        self.elapsed += 1;
+        if self.elapsed >= instruction.cycles() {
+            instruction.execute(&mut self.registers);
+            self.pc += 1;
+            self.elapsed = 0;
+        }
+        true
+    }
+
+    pub fn run(&mut self, observers: &mut [&mut dyn Observer]) {
+        while self.tick(observers) {}
+    }
+}
@@ -0,0 +1,89 @@
+use super::cpu::*;
+use crate::parser::*;
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Breakpoint {
+    Cycle(u64),
+    Register(char, i64),
+}
+
+impl Breakpoint {
+    fn hit(&self, before: &Registers, cpu: &Cpu) -> bool {
+        match *self {
+            Breakpoint::Cycle(cycle) => cpu.cycle + 1 == cycle,
+            Breakpoint::Register(register, value) => {
+                before.get(&register) != Some(&value)
+                    && cpu.registers.get(&register) == Some(&value)
+            }
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some(("cycle", cycle)) => Ok(Breakpoint::Cycle(arg(cycle)?)),
+            Some((register, value)) if register.chars().count() == 1 => Ok(Breakpoint::Register(
+                register.chars().next().unwrap(),
+                arg(value)?,
+            )),
+            _ => error(&format!("Failed to parse breakpoint: {}", s)),
+        }
+    }
+}
+
+pub struct Debugger {
+    pub cpu: Cpu,
+    pub breakpoints: Vec<Breakpoint>,
+    pub trace: Vec<(u64, Registers)>,
+}
+
+impl Debugger {
+    pub fn new(cpu: Cpu) -> Self {
+        Debugger {
+            cpu,
+            breakpoints: vec![],
+            trace: vec![],
+        }
+    }
+
+    pub fn step(&mut self, observers: &mut [&mut dyn Observer]) -> bool {
+        let running = self.cpu.tick(observers);
+        if running {
+            self.trace
+                .push((self.cpu.cycle, self.cpu.registers.clone()));
+        }
+        running
+    }
+
+    pub fn resume(&mut self, observers: &mut [&mut dyn Observer]) -> Option<Breakpoint> {
+        loop {
+            let before = self.cpu.registers.clone();
+            if !self.step(observers) {
+                return None;
+            }
+            if let Some(b) = self.breakpoints.iter().find(|b| b.hit(&before, &self.cpu)) {
+                return Some(*b);
+            }
+        }
+    }
+
+    pub fn write_trace(&self, out: &mut impl Write) -> io::Result<()> {
+        let registers: Vec<char> = self.cpu.registers.keys().copied().collect();
+        let header: Vec<String> = registers.iter().map(|r| r.to_string()).collect();
+        writeln!(out, "cycle,{}", header.join(","))?;
+        for (cycle, values) in &self.trace {
+            let values: Vec<String> = registers
+                .iter()
+                .map(|r| values.get(r).copied().unwrap_or(0).to_string())
+                .collect();
+            writeln!(out, "{},{}", cycle, values.join(","))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::parser::*;
+use std::io;
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+pub const GLYPH_SPACING: usize = 5;
+
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+pub fn decode(image: &str) -> io::Result<String> {
+    let rows: Vec<&str> = image.lines().filter(|l| !l.is_empty()).collect();
+    if rows.len() != GLYPH_HEIGHT {
+        return error(&format!(
+            "Expected {} rows but found {}",
+            GLYPH_HEIGHT,
+            rows.len()
+        ));
+    }
+    if rows.iter().any(|r| !r.is_ascii()) {
+        return error("Image contains non-ASCII characters; use # and .");
+    }
+    let width = rows[0].len();
+    if rows.iter().any(|r| r.len() != width) {
+        return error("Image rows have different widths");
+    }
+    let mut letters = String::new();
+    for start in (0..width).step_by(GLYPH_SPACING) {
+        let end = (start + GLYPH_WIDTH).min(width);
+        let glyph: Vec<&str> = rows.iter().map(|r| &r[start..end]).collect();
+        match FONT.iter().find(|(_, g)| g[..] == glyph[..]) {
+            Some((letter, _)) => letters.push(*letter),
+            None if glyph.iter().all(|r| !r.contains('#')) => letters.push(' '),
+            None => error(&format!(
+                "Unrecognised glyph at column {}:\n{}",
+                start,
+                glyph.join("\n")
+            ))?,
+        }
+    }
+    Ok(String::from(letters.trim_end()))
+}
+
+pub fn glyph(letter: char) -> Option<[&'static str; GLYPH_HEIGHT]> {
+    FONT.iter().find(|(l, _)| *l == letter).map(|(_, g)| *g)
+}
@@ -1,37 +1,56 @@
+mod expression;
+mod rns;
+mod simulator;
+mod trajectory;
+
 use crate::parser::*;
+use expression::*;
+use rns::ResidueSystem;
+use simulator::*;
 use std::io;
+use std::io::Write;
+use std::str::FromStr;
+use trajectory::Trajectory;
 
 const INPUT_FILE: &str = "input/input11.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input11_sample.txt";
 
-#[derive(Debug)]
-enum Operand {
-    Symbol,
-    Number(u64),
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Test {
+    DivisibleBy(u64),
+    GreaterThan(u64),
+    LessThan(u64),
+    EqualTo(u64),
 }
 
-impl From<&str> for Operand {
-    fn from(s: &str) -> Self {
-        match s {
-            "old" => Operand::Symbol,
-            _ => Operand::Number(s.parse().unwrap()),
+impl Test {
+    fn check(&self, worry: u64) -> bool {
+        match *self {
+            Test::DivisibleBy(d) => worry.is_multiple_of(d),
+            Test::GreaterThan(n) => worry > n,
+            Test::LessThan(n) => worry < n,
+            Test::EqualTo(n) => worry == n,
         }
     }
 }
 
-#[derive(Debug)]
-enum Operation {
-    Add(Operand),
-    Multiply(Operand),
-}
-
-impl Operation {
-    fn apply(&self, old: u64) -> u64 {
-        match self {
-            Operation::Add(Operand::Symbol) => old + old,
-            Operation::Add(Operand::Number(x)) => old + x,
-            Operation::Multiply(Operand::Symbol) => old * old,
-            Operation::Multiply(Operand::Number(x)) => old * x,
+impl FromStr for Test {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (predicate, value) = match s.rsplit_once(' ') {
+            Some(split) => split,
+            None => return error(&format!("Failed to parse test: {}", s)),
+        };
+        match predicate {
+            "divisible by" => match arg(value)? {
+                0 => error("Cannot test divisibility by 0"),
+                d => Ok(Test::DivisibleBy(d)),
+            },
+            "greater than" => Ok(Test::GreaterThan(arg(value)?)),
+            "less than" => Ok(Test::LessThan(arg(value)?)),
+            "equal to" => Ok(Test::EqualTo(arg(value)?)),
+            _ => error(&format!("Unknown test: {}", s)),
         }
     }
 }
@@ -40,8 +59,8 @@ impl Operation {
 struct Monkey {
     items: Vec<u64>,
     inspection_count: u64,
-    operation: Operation,
-    divisor: u64,
+    operation: Expr,
+    test: Test,
     next_if_true: usize,
     next_if_false: usize,
 }
@@ -51,8 +70,8 @@ impl Monkey {
         Self {
             items: Vec::new(),
             inspection_count: 0,
-            operation: Operation::Add(Operand::Number(0)),
-            divisor: 1,
+            operation: Expr::Old,
+            test: Test::DivisibleBy(1),
             next_if_true: 0,
             next_if_false: 0,
         }
@@ -80,26 +99,14 @@ impl TryFrom<&mut FileLines> for Input {
                         monkey.items.push(item);
                     }
                 }
-                Some("Operation:") => {
-                    words.next();
-                    words.next();
-                    words.next();
-                    let op = words.next();
-                    let operand = match words.next() {
-                        Some(s) => Operand::from(s),
-                        None => error("Failed to parse operand")?,
-                    };
-                    match op {
-                        Some("+") => monkey.operation = Operation::Add(operand),
-                        Some("*") => monkey.operation = Operation::Multiply(operand),
-                        _ => error("Failed to parse operator")?,
-                    };
-                }
-                Some("Test:") => {
-                    words.next();
-                    words.next();
-                    monkey.divisor = words.next().unwrap().parse().unwrap();
-                }
+                Some("Operation:") => match line.trim().strip_prefix("Operation: new = ") {
+                    Some(expr) => monkey.operation = Expr::from_str(expr)?,
+                    None => error(&format!("Failed to parse operation: {}", line))?,
+                },
+                Some("Test:") => match line.trim().strip_prefix("Test: ") {
+                    Some(test) => monkey.test = Test::from_str(test)?,
+                    None => error(&format!("Failed to parse test: {}", line))?,
+                },
                 Some("If") => match words.next() {
                     Some("true:") => {
                         words.next();
@@ -124,90 +131,300 @@ impl TryFrom<&mut FileLines> for Input {
     }
 }
 
-pub fn part_1() -> io::Result<u64> {
+pub fn part_1() -> io::Result<u128> {
     do_part_1(INPUT_FILE)
 }
 
-fn _part_1_sample() -> io::Result<u64> {
+pub fn _part_1_sample() -> io::Result<u128> {
     do_part_1(_INPUT_FILE_SAMPLE)
 }
 
-fn do_part_1(input_file: &str) -> io::Result<u64> {
+fn do_part_1(input_file: &str) -> io::Result<u128> {
     let input = Input::try_from(&mut FileLines::new(input_file)?)?;
-    let mut monkeys = input.monkeys;
+    let mut simulator = Simulator::new(input.monkeys, Some(3))?;
     for _ in 0..20 {
-        for i in 0..monkeys.len() {
-            process_items(&mut monkeys, i);
-        }
+        simulator.round()?;
     }
-    monkeys.sort_by(|a, b| b.inspection_count.cmp(&a.inspection_count));
-    let monkey_business = monkeys
-        .iter()
-        .map(|monkey| monkey.inspection_count)
-        .take(2)
-        .product();
-    Ok(monkey_business)
+    Ok(monkey_business(&simulator.inspections()))
 }
 
-pub fn part_2() -> io::Result<u64> {
+pub fn part_2() -> io::Result<u128> {
     do_part_2(INPUT_FILE)
 }
 
-fn _part_2_sample() -> io::Result<u64> {
+pub fn _part_2_sample() -> io::Result<u128> {
     do_part_2(_INPUT_FILE_SAMPLE)
 }
 
-fn do_part_2(input_file: &str) -> io::Result<u64> {
+fn do_part_2(input_file: &str) -> io::Result<u128> {
     let input = Input::try_from(&mut FileLines::new(input_file)?)?;
-    let mut monkeys = input.monkeys;
-    let factor: u64 = monkeys.iter().map(|m| m.divisor).product();
-    for _ in 0..10000 {
-        for i in 0..monkeys.len() {
-            process_items_reduced_worry(&mut monkeys, i, factor);
+    Ok(monkey_business(&totals(
+        &trajectories(&input.monkeys)?,
+        10000,
+    )))
+}
+
+fn monkey_business(inspections: &[u64]) -> u128 {
+    let mut inspections = inspections.to_vec();
+    inspections.sort_by(|a, b| b.cmp(a));
+    inspections.iter().take(2).map(|n| u128::from(*n)).product()
+}
+
+fn residue_system(monkeys: &[Monkey]) -> io::Result<ResidueSystem> {
+    let mut moduli = vec![];
+    for (i, monkey) in monkeys.iter().enumerate() {
+        let modular = monkey
+            .operation
+            .operators()
+            .iter()
+            .all(|op| matches!(op, Operator::Add | Operator::Multiply));
+        match monkey.test {
+            Test::DivisibleBy(d) if modular => moduli.push(d),
+            _ => error(&format!(
+                "Monkey {} cannot use residue worry with {} and {:?}",
+                i, monkey.operation, monkey.test
+            ))?,
+        }
+    }
+    Ok(ResidueSystem::new(moduli))
+}
+
+fn trajectories(monkeys: &[Monkey]) -> io::Result<Vec<Trajectory>> {
+    let rns = residue_system(monkeys)?;
+    let mut trajectories = vec![];
+    for (i, monkey) in monkeys.iter().enumerate() {
+        for item in &monkey.items {
+            trajectories.push(Trajectory::trace(monkeys, &rns, i, *item)?);
+        }
+    }
+    Ok(trajectories)
+}
+
+fn totals(trajectories: &[Trajectory], rounds: u64) -> Vec<u64> {
+    let mut totals = vec![];
+    for trajectory in trajectories {
+        let item_totals = trajectory.totals(rounds);
+        totals.resize(item_totals.len(), 0);
+        for (total, n) in totals.iter_mut().zip(item_totals) {
+            *total += n;
         }
     }
-    monkeys.sort_by(|a, b| b.inspection_count.cmp(&a.inspection_count));
-    let monkey_business = monkeys
-        .iter()
-        .map(|monkey| monkey.inspection_count)
-        .take(2)
-        .product();
-    Ok(monkey_business)
-}
-
-fn process_items(monkeys: &mut Vec<Monkey>, i: usize) {
-    for _ in 0..monkeys[i].items.len() {
-        monkeys[i].inspection_count += 1;
-        let item = monkeys[i].items.pop().unwrap();
-        let operated = monkeys[i].operation.apply(item) / 3;
-        if operated % monkeys[i].divisor == 0 {
-            let next = monkeys[i].next_if_true;
-            monkeys[next].items.push(operated);
-        } else {
-            let next = monkeys[i].next_if_false;
-            monkeys[next].items.push(operated);
+    totals
+}
+
+fn write_history(trajectories: &[Trajectory], rounds: u64, out: &mut impl Write) -> io::Result<()> {
+    let monkey_count = trajectories.first().map_or(0, |t| t.rounds[0].len());
+    let header: Vec<String> = (0..monkey_count).map(|i| format!("monkey_{}", i)).collect();
+    writeln!(out, "round,{}", header.join(","))?;
+    for round in 0..rounds {
+        let mut inspections = vec![0; monkey_count];
+        for trajectory in trajectories {
+            for (total, n) in inspections.iter_mut().zip(trajectory.round(round)) {
+                *total += n;
+            }
         }
+        let inspections: Vec<String> = inspections.iter().map(|n| n.to_string()).collect();
+        writeln!(out, "{},{}", round + 1, inspections.join(","))?;
     }
+    Ok(())
 }
 
-fn process_items_reduced_worry(monkeys: &mut Vec<Monkey>, i: usize, factor: u64) {
-    for _ in 0..monkeys[i].items.len() {
-        monkeys[i].inspection_count += 1;
-        let item = monkeys[i].items.pop().unwrap();
-        let operated = monkeys[i].operation.apply(item) % factor;
-        if operated % monkeys[i].divisor == 0 {
-            let next = monkeys[i].next_if_true;
-            monkeys[next].items.push(operated);
-        } else {
-            let next = monkeys[i].next_if_false;
-            monkeys[next].items.push(operated);
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    let load = |rest: &[&str]| Input::try_from(&mut FileLines::new(file_arg(rest, INPUT_FILE))?);
+    match args {
+        ["business", rounds, rest @ ..] => {
+            let trajectories = trajectories(&load(rest)?.monkeys)?;
+            let totals = totals(&trajectories, arg(rounds)?);
+            for (i, total) in totals.iter().enumerate() {
+                println!("Monkey {}: {}", i, total);
+            }
+            println!("Monkey business: {}", monkey_business(&totals));
+        }
+        ["history", rounds, rest @ ..] => {
+            let trajectories = trajectories(&load(rest)?.monkeys)?;
+            write_history(&trajectories, arg(rounds)?, &mut io::stdout())?;
+        }
+        ["cycles", rest @ ..] => {
+            for (i, trajectory) in trajectories(&load(rest)?.monkeys)?.iter().enumerate() {
+                println!(
+                    "Item {}: cycle of {} rounds from round {}",
+                    i,
+                    trajectory.cycle_length(),
+                    trajectory.cycle_start + 1
+                );
+            }
         }
+        ["simulate", rounds, rest @ ..] => {
+            let (options, file) = match rest.iter().position(|a| *a == "--") {
+                Some(i) => (&rest[..i], &rest[i + 1..]),
+                None => (rest, &[][..]),
+            };
+            let mut monkeys = load(file)?.monkeys;
+            let mut relief = Some(3);
+            for option in options {
+                match option.strip_prefix("relief=") {
+                    Some("none") => relief = None,
+                    Some(d) => relief = Some(arg(d)?),
+                    None => apply_override(&mut monkeys, option)?,
+                }
+            }
+            let mut simulator = Simulator::new(monkeys, relief)?;
+            println!("round,from,to,before,after");
+            for _ in 0..arg(rounds)? {
+                for e in simulator.round()? {
+                    println!("{},{},{},{},{}", e.round, e.from, e.to, e.before, e.after);
+                }
+            }
+            let inspections = simulator.inspections();
+            eprintln!("Inspections: {:?}", inspections);
+            eprintln!("Monkey business: {}", monkey_business(&inspections));
+        }
+        _ => error(
+            "Usage: 11 (business <rounds>|history <rounds>|cycles) [file] \
+             | simulate <rounds> [relief=<n|none>] [<monkey>:<field>=<value> ...] [-- file]",
+        )?,
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    use super::{
+        _part_1_sample, _part_2_sample, apply_override, do_part_1, do_part_2, monkey_business,
+        part_1, part_2, totals, trajectories, write_history, Event, Expr, FileLines, Input,
+        Simulator, Test, _INPUT_FILE_SAMPLE,
+    };
+    use std::str::FromStr;
+
+    const INPUT_FILE_VARIANT: &str = "input/input11_sample_variant.txt";
+
+    #[test]
+    fn test_expression() {
+        let expr = Expr::from_str("(old * 19 - 4) % 1000 + old/2").unwrap();
+        assert_eq!("((((old * 19) - 4) % 1000) + (old / 2))", expr.to_string());
+        assert_eq!(Some(205 + 5), expr.eval(11));
+        assert_eq!(
+            Some(30),
+            Expr::from_str("2 * (3 + old) * 3").unwrap().eval(2)
+        );
+        assert_eq!(Some(1), Expr::from_str("10 - old - 4").unwrap().eval(5));
+        assert_eq!(None, Expr::from_str("old - 4").unwrap().eval(3));
+        assert_eq!(None, Expr::from_str("1 / (old - 3)").unwrap().eval(3));
+        assert_eq!(None, Expr::from_str("old * old").unwrap().eval(u64::MAX));
+        for invalid in ["", "old +", "(old", "old old", "new + 1", "old ^ 2"] {
+            assert!(Expr::from_str(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_predicates() {
+        assert_eq!(
+            Test::GreaterThan(5),
+            Test::from_str("greater than 5").unwrap()
+        );
+        assert!(Test::from_str("divisible by 3").unwrap().check(9));
+        assert!(!Test::from_str("less than 3").unwrap().check(3));
+        assert!(Test::from_str("equal to 3").unwrap().check(3));
+        assert!(Test::from_str("divisible by 0").is_err());
+        assert!(Test::from_str("odd").is_err());
+    }
+
+    #[test]
+    fn test_cycles() {
+        let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE).unwrap()).unwrap();
+        let trajectories = trajectories(&input.monkeys).unwrap();
+        assert_eq!(vec![2, 4, 3, 6], totals(&trajectories, 1));
+        assert_eq!(vec![99, 97, 8, 103], totals(&trajectories, 20));
+        assert_eq!(
+            vec![52166, 47830, 1938, 52013],
+            totals(&trajectories, 10000)
+        );
+        let trillion = totals(&trajectories, 1_000_000_000_000);
+        let inspections: u64 = trillion.iter().sum();
+        assert!((10 * 1_000_000_000_000..=40 * 1_000_000_000_000).contains(&inspections));
+        assert_eq!(2713310158, monkey_business(&totals(&trajectories, 10000)));
+
+        let mut out = vec![];
+        write_history(&trajectories, 20, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            Some("round,monkey_0,monkey_1,monkey_2,monkey_3"),
+            out.lines().next()
+        );
+        assert_eq!(Some("1,2,4,3,6"), out.lines().nth(1));
+        assert_eq!(21, out.lines().count());
+
+        let mut monkeys = sample().monkeys;
+        apply_override(&mut monkeys, "0:operation=old - 4").unwrap();
+        assert!(super::trajectories(&monkeys).is_err());
+        assert!(Simulator::new(monkeys, None).is_err());
+    }
+
+    fn sample() -> Input {
+        Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_simulator() {
+        let mut simulator = Simulator::new(sample().monkeys, Some(3)).unwrap();
+        let events = simulator.round().unwrap();
+        assert_eq!(
+            Event {
+                round: 1,
+                from: 0,
+                to: 3,
+                before: 79,
+                after: 500,
+            },
+            events[0]
+        );
+        assert_eq!((98, 620), (events[1].before, events[1].after));
+        assert_eq!(14, events.len());
+        assert_eq!(vec![2, 4, 3, 5], simulator.inspections());
+        assert_eq!(2, simulator.round().unwrap()[0].round);
+
+        let mut simulator = Simulator::new(sample().monkeys, None).unwrap();
+        for _ in 0..1000 {
+            simulator.round().unwrap();
+        }
+        assert_eq!(vec![5204, 4792, 199, 5192], simulator.inspections());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut monkeys = sample().monkeys;
+        apply_override(&mut monkeys, "0:divisor=7").unwrap();
+        apply_override(&mut monkeys, "1:operation=old * 2 + 1").unwrap();
+        apply_override(&mut monkeys, "2:targets=0,1").unwrap();
+        apply_override(&mut monkeys, "3:test=greater than 100").unwrap();
+        assert_eq!(Test::DivisibleBy(7), monkeys[0].test);
+        assert_eq!(Some(11), monkeys[1].operation.eval(5));
+        assert_eq!((0, 1), (monkeys[2].next_if_true, monkeys[2].next_if_false));
+        assert!(Simulator::new(monkeys, None).is_err());
+
+        let mut monkeys = sample().monkeys;
+        for invalid in [
+            "0:divisor=0",
+            "9:divisor=3",
+            "0:colour=red",
+            "0divisor",
+            "0:targets=1",
+        ] {
+            assert!(
+                apply_override(&mut monkeys, invalid).is_err(),
+                "{}",
+                invalid
+            );
+        }
+        apply_override(&mut monkeys, "0:targets=1,4").unwrap();
+        assert!(Simulator::new(monkeys, Some(3)).is_err());
+    }
+
+    #[test]
+    fn test_variant() {
+        assert_eq!(10480, do_part_1(INPUT_FILE_VARIANT).unwrap());
+        assert!(do_part_2(INPUT_FILE_VARIANT).is_err());
+    }
 
     #[test]
     fn test_part_1() {
@@ -0,0 +1,179 @@
+use crate::parser::*;
+use std::fmt;
+use std::io;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl Operator {
+    fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+            Operator::Remainder => '%',
+        }
+    }
+
+    fn apply(&self, a: u64, b: u64) -> Option<u64> {
+        match self {
+            Operator::Add => a.checked_add(b),
+            Operator::Subtract => a.checked_sub(b),
+            Operator::Multiply => a.checked_mul(b),
+            Operator::Divide => a.checked_div(b),
+            Operator::Remainder => a.checked_rem(b),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Old,
+    Constant(u64),
+    Binary(Box<Expr>, Operator, Box<Expr>),
+}
+
+impl Expr {
+    pub fn eval(&self, old: u64) -> Option<u64> {
+        match self {
+            Expr::Old => Some(old),
+            Expr::Constant(c) => Some(*c),
+            Expr::Binary(a, op, b) => op.apply(a.eval(old)?, b.eval(old)?),
+        }
+    }
+
+    pub fn eval_mod(&self, old: u64, modulus: u64) -> Option<u64> {
+        match self {
+            Expr::Old => Some(old % modulus),
+            Expr::Constant(c) => Some(c % modulus),
+            Expr::Binary(a, op, b) => {
+                let (a, b) = (a.eval_mod(old, modulus)?, b.eval_mod(old, modulus)?);
+                let result = match op {
+                    Operator::Add => (u128::from(a) + u128::from(b)) % u128::from(modulus),
+                    Operator::Multiply => u128::from(a) * u128::from(b) % u128::from(modulus),
+                    Operator::Subtract | Operator::Divide | Operator::Remainder => return None,
+                };
+                Some(result as u64)
+            }
+        }
+    }
+
+    pub fn operators(&self) -> Vec<Operator> {
+        match self {
+            Expr::Old | Expr::Constant(_) => vec![],
+            Expr::Binary(a, op, b) => {
+                let mut operators = a.operators();
+                operators.push(*op);
+                operators.extend(b.operators());
+                operators
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Old => write!(f, "old"),
+            Expr::Constant(c) => write!(f, "{}", c),
+            Expr::Binary(a, op, b) => write!(f, "({} {} {})", a, op.symbol(), b),
+        }
+    }
+}
+
+struct ExprParser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl ExprParser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn binary(
+        &mut self,
+        operators: &[Operator],
+        operand: fn(&mut Self) -> io::Result<Expr>,
+    ) -> io::Result<Expr> {
+        let mut expr = operand(self)?;
+        while let Some(c) = self.peek() {
+            match operators.iter().find(|op| op.symbol() == c) {
+                Some(op) => {
+                    self.chars.next();
+                    expr = Expr::Binary(Box::new(expr), *op, Box::new(operand(self)?));
+                }
+                None => break,
+            }
+        }
+        Ok(expr)
+    }
+
+    fn expr(&mut self) -> io::Result<Expr> {
+        self.binary(&[Operator::Add, Operator::Subtract], Self::term)
+    }
+
+    fn term(&mut self) -> io::Result<Expr> {
+        let operators = [Operator::Multiply, Operator::Divide, Operator::Remainder];
+        self.binary(&operators, Self::factor)
+    }
+
+    fn factor(&mut self) -> io::Result<Expr> {
+        match self.peek() {
+            Some('(') => {
+                self.chars.next();
+                let expr = self.expr()?;
+                match self.peek() {
+                    Some(')') => {
+                        self.chars.next();
+                        Ok(expr)
+                    }
+                    _ => error("Expected )"),
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut digits = String::new();
+                while let Some(d) = self.chars.next_if(|c| c.is_ascii_digit()) {
+                    digits.push(d);
+                }
+                Ok(Expr::Constant(arg(&digits)?))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let mut word = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphanumeric()) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "old" => Ok(Expr::Old),
+                    _ => error(&format!("Unknown variable: {}", word)),
+                }
+            }
+            Some(c) => error(&format!("Unexpected character: {}", c)),
+            None => error("Unexpected end of expression"),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = ExprParser {
+            chars: s.chars().peekable(),
+        };
+        let expr = parser.expr()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(c) => error(&format!("Unexpected character: {}", c)),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use super::expression::Expr;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Worry {
+    residues: Vec<u64>,
+}
+
+#[derive(Debug)]
+pub struct ResidueSystem {
+    moduli: Vec<u64>,
+}
+
+impl ResidueSystem {
+    pub fn new(moduli: Vec<u64>) -> Self {
+        ResidueSystem { moduli }
+    }
+
+    pub fn modulus(&self) -> Option<u64> {
+        self.moduli
+            .iter()
+            .try_fold(1u64, |product, m| product.checked_mul(*m))
+    }
+
+    pub fn encode(&self, value: u64) -> Worry {
+        Worry {
+            residues: self.moduli.iter().map(|m| value % m).collect(),
+        }
+    }
+
+    pub fn apply(&self, expr: &Expr, worry: &Worry) -> Option<Worry> {
+        let mut residues = Vec::with_capacity(self.moduli.len());
+        for (r, m) in worry.residues.iter().zip(&self.moduli) {
+            residues.push(expr.eval_mod(*r, *m)?);
+        }
+        Some(Worry { residues })
+    }
+
+    pub fn is_divisible(&self, worry: &Worry, i: usize) -> bool {
+        worry.residues[i] == 0
+    }
+}
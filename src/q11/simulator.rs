@@ -0,0 +1,115 @@
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Event {
+    pub round: u64,
+    pub from: usize,
+    pub to: usize,
+    pub before: u64,
+    pub after: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Relief {
+    DivideBy(u64),
+    Modulo(u64),
+}
+
+pub struct Simulator {
+    pub monkeys: Vec<Monkey>,
+    relief: Relief,
+    round: u64,
+}
+
+impl Simulator {
+    pub fn new(monkeys: Vec<Monkey>, relief: Option<u64>) -> io::Result<Self> {
+        for (i, monkey) in monkeys.iter().enumerate() {
+            let target = monkey.next_if_true.max(monkey.next_if_false);
+            if target >= monkeys.len() {
+                return error(&format!("Monkey {} throws to missing monkey {}", i, target));
+            }
+        }
+        let relief = match relief {
+            Some(0) => return error("Relief divisor must be positive"),
+            Some(d) => Relief::DivideBy(d),
+            None => match residue_system(&monkeys)?.modulus() {
+                Some(m) => Relief::Modulo(m),
+                None => return error("Product of divisors overflows"),
+            },
+        };
+        Ok(Simulator {
+            monkeys,
+            relief,
+            round: 0,
+        })
+    }
+
+    pub fn round(&mut self) -> io::Result<Vec<Event>> {
+        self.round += 1;
+        let mut events = vec![];
+        for from in 0..self.monkeys.len() {
+            let items: Vec<u64> = self.monkeys[from].items.drain(..).collect();
+            for before in items {
+                let monkey = &mut self.monkeys[from];
+                monkey.inspection_count += 1;
+                let inspected = match monkey.operation.eval(before) {
+                    Some(worry) => worry,
+                    None => error(&format!(
+                        "Cannot evaluate {} for old = {}",
+                        monkey.operation, before
+                    ))?,
+                };
+                let after = match self.relief {
+                    Relief::DivideBy(d) => inspected / d,
+                    Relief::Modulo(m) => inspected % m,
+                };
+                let to = if monkey.test.check(after) {
+                    monkey.next_if_true
+                } else {
+                    monkey.next_if_false
+                };
+                self.monkeys[to].items.push(after);
+                events.push(Event {
+                    round: self.round,
+                    from,
+                    to,
+                    before,
+                    after,
+                });
+            }
+        }
+        Ok(events)
+    }
+
+    pub fn inspections(&self) -> Vec<u64> {
+        self.monkeys.iter().map(|m| m.inspection_count).collect()
+    }
+}
+
+pub fn apply_override(monkeys: &mut [Monkey], spec: &str) -> io::Result<()> {
+    let (i, field, value) = match spec
+        .split_once(':')
+        .map(|(i, rest)| (i, rest.split_once('=')))
+    {
+        Some((i, Some((field, value)))) => (arg::<usize>(i)?, field, value),
+        _ => return error(&format!("Failed to parse override: {}", spec)),
+    };
+    let monkey = match monkeys.get_mut(i) {
+        Some(monkey) => monkey,
+        None => return error(&format!("No monkey {}", i)),
+    };
+    match field {
+        "divisor" => monkey.test = Test::from_str(&format!("divisible by {}", value))?,
+        "test" => monkey.test = Test::from_str(value)?,
+        "operation" => monkey.operation = Expr::from_str(value)?,
+        "targets" => match value.split_once(',') {
+            Some((if_true, if_false)) => {
+                monkey.next_if_true = arg(if_true)?;
+                monkey.next_if_false = arg(if_false)?;
+            }
+            None => error(&format!("Targets must be <true>,<false>: {}", value))?,
+        },
+        _ => error(&format!("Unknown override field: {}", field))?,
+    }
+    Ok(())
+}
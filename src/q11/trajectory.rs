@@ -0,0 +1,91 @@
+use super::rns::*;
+use super::Monkey;
+use crate::parser::*;
+use std::collections::HashMap;
+use std::io;
+
+#[derive(Debug)]
+pub struct Trajectory {
+    pub rounds: Vec<Vec<u64>>,
+    pub cycle_start: usize,
+}
+
+impl Trajectory {
+    pub fn trace(
+        monkeys: &[Monkey],
+        rns: &ResidueSystem,
+        start: usize,
+        item: u64,
+    ) -> io::Result<Self> {
+        let mut seen: HashMap<(usize, Worry), usize> = HashMap::new();
+        let mut rounds = vec![];
+        let mut state = (start, rns.encode(item));
+        loop {
+            if let Some(&cycle_start) = seen.get(&state) {
+                return Ok(Trajectory {
+                    rounds,
+                    cycle_start,
+                });
+            }
+            seen.insert(state.clone(), rounds.len());
+            let mut inspections = vec![0; monkeys.len()];
+            let (mut holder, mut worry) = state;
+            loop {
+                inspections[holder] += 1;
+                let monkey = &monkeys[holder];
+                worry = match rns.apply(&monkey.operation, &worry) {
+                    Some(worry) => worry,
+                    None => error(&format!("Cannot evaluate {} on residues", monkey.operation))?,
+                };
+                let next = if rns.is_divisible(&worry, holder) {
+                    monkey.next_if_true
+                } else {
+                    monkey.next_if_false
+                };
+                let same_round = next > holder;
+                holder = next;
+                if !same_round {
+                    break;
+                }
+            }
+            rounds.push(inspections);
+            state = (holder, worry);
+        }
+    }
+
+    pub fn cycle_length(&self) -> usize {
+        self.rounds.len() - self.cycle_start
+    }
+
+    pub fn round(&self, round: u64) -> &[u64] {
+        let index = match usize::try_from(round) {
+            Ok(r) if r < self.cycle_start => r,
+            _ => {
+                let offset = (round - self.cycle_start as u64) % self.cycle_length() as u64;
+                self.cycle_start + offset as usize
+            }
+        };
+        &self.rounds[index]
+    }
+
+    pub fn totals(&self, rounds: u64) -> Vec<u64> {
+        let mut totals = vec![0; self.rounds[0].len()];
+        let add = |totals: &mut Vec<u64>, range: &[Vec<u64>], times: u64| {
+            for inspections in range {
+                for (total, n) in totals.iter_mut().zip(inspections) {
+                    *total += n * times;
+                }
+            }
+        };
+        let prefix = rounds.min(self.cycle_start as u64) as usize;
+        add(&mut totals, &self.rounds[..prefix], 1);
+        if rounds > self.cycle_start as u64 {
+            let remaining = rounds - self.cycle_start as u64;
+            let length = self.cycle_length() as u64;
+            let cycle = &self.rounds[self.cycle_start..];
+            add(&mut totals, cycle, remaining / length);
+            add(&mut totals, &cycle[..(remaining % length) as usize], 1);
+        }
+        totals
+    }
+}
@@ -1,176 +1,373 @@
+mod hiker;
+
 use crate::parser::*;
-use std::collections::{HashMap, HashSet};
+use hiker::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::io;
 
 const INPUT_FILE: &str = "input/input12.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input12_sample.txt";
 
-type Map = Vec<Vec<PointInfo>>;
 type Point = (usize, usize);
 
 struct Input {
-    map: Map,
+    heights: Vec<Vec<u8>>,
+    start: Point,
+    end: Point,
 }
 
 impl TryFrom<FileLines> for Input {
     type Error = io::Error;
 
     fn try_from(lines: FileLines) -> Result<Self, Self::Error> {
-        let mut map = Vec::new();
-        for (_, line) in lines.enumerate() {
+        let mut heights = Vec::new();
+        let (mut start, mut end) = (None, None);
+        for (row, line) in lines.enumerate() {
             let mut r = Vec::new();
-            for (_, c) in line.bytes().enumerate() {
-                r.push(PointInfo {
-                    elevation: c,
-                    f_score: usize::MAX,
-                    g_score: usize::MAX,
+            for (column, c) in line.bytes().enumerate() {
+                r.push(match c {
+                    b'S' if start.is_none() => {
+                        start = Some((row, column));
+                        b'a'
+                    }
+                    b'E' if end.is_none() => {
+                        end = Some((row, column));
+                        b'z'
+                    }
+                    b'a'..=b'z' => c,
+                    _ => error(&format!("Unexpected {} at {:?}", c as char, (row, column)))?,
                 });
             }
-            map.push(r);
+            if heights
+                .first()
+                .is_some_and(|first: &Vec<u8>| first.len() != r.len())
+            {
+                error(&format!("Row {} has a different width", row))?;
+            }
+            heights.push(r);
+        }
+        match (start, end) {
+            (Some(start), Some(end)) => Ok(Input {
+                heights,
+                start,
+                end,
+            }),
+            _ => error("Height map needs one S and one E"),
         }
-        Ok(Input { map })
     }
 }
 
-struct PointInfo {
-    elevation: u8,
-    f_score: usize,
-    g_score: usize,
+impl Input {
+    fn height(&self, point: Point) -> u8 {
+        self.heights[point.0][point.1]
+    }
+
+    fn neighbours(&self, point: Point, diagonal: bool) -> Vec<Point> {
+        let (rows, columns) = (self.heights.len() as i64, self.heights[0].len() as i64);
+        let mut result = vec![];
+        for (dr, dc) in [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+            (1, 1),
+        ] {
+            if dr != 0 && dc != 0 && !diagonal {
+                continue;
+            }
+            let (row, column) = (point.0 as i64 + dr, point.1 as i64 + dc);
+            if (0..rows).contains(&row) && (0..columns).contains(&column) {
+                result.push((row as usize, column as usize));
+            }
+        }
+        result
+    }
+
+    fn a_star(&self, rules: &dyn Rules, start: Point, goal: Point) -> Option<(Vec<Point>, usize)> {
+        let (rows, columns) = (self.heights.len(), self.heights[0].len());
+        let mut g_score = vec![vec![usize::MAX; columns]; rows];
+        let mut came_from: Vec<Vec<Option<Point>>> = vec![vec![None; columns]; rows];
+        let mut open_set = BinaryHeap::new();
+        let cheapest = cheapest_step(rules);
+        let heuristic = |node: Point| cheapest * heuristic(node, goal, rules.diagonal());
+        g_score[start.0][start.1] = 0;
+        open_set.push(Reverse((heuristic(start), 0, start)));
+
+        while let Some(Reverse((_, g, current))) = open_set.pop() {
+            if current == goal {
+                let mut path = vec![goal];
+                while let Some(previous) = came_from[path[path.len() - 1].0][path[path.len() - 1].1]
+                {
+                    path.push(previous);
+                }
+                path.reverse();
+                return Some((path, g));
+            }
+            if g > g_score[current.0][current.1] {
+                continue;
+            }
+            for neighbour in self.neighbours(current, rules.diagonal()) {
+                let (from, to) = (self.height(current), self.height(neighbour));
+                if !rules.can_step(from, to) {
+                    continue;
+                }
+                let new_score = g + rules.cost(from, to);
+                if new_score < g_score[neighbour.0][neighbour.1] {
+                    came_from[neighbour.0][neighbour.1] = Some(current);
+                    g_score[neighbour.0][neighbour.1] = new_score;
+                    open_set.push(Reverse((
+                        new_score + heuristic(neighbour),
+                        new_score,
+                        neighbour,
+                    )));
+                }
+            }
+        }
+        None
+    }
+
+    fn costs_to(&self, rules: &dyn Rules, goal: Point) -> Vec<Vec<Option<usize>>> {
+        let mut costs = vec![vec![None; self.heights[0].len()]; self.heights.len()];
+        let mut open_set = BinaryHeap::from([Reverse((0, goal))]);
+        costs[goal.0][goal.1] = Some(0);
+        while let Some(Reverse((cost, current))) = open_set.pop() {
+            if costs[current.0][current.1].is_some_and(|c| c < cost) {
+                continue;
+            }
+            for neighbour in self.neighbours(current, rules.diagonal()) {
+                let (from, to) = (self.height(neighbour), self.height(current));
+                if !rules.can_step(from, to) {
+                    continue;
+                }
+                let new_cost = cost + rules.cost(from, to);
+                if costs[neighbour.0][neighbour.1].is_none_or(|c| new_cost < c) {
+                    costs[neighbour.0][neighbour.1] = Some(new_cost);
+                    open_set.push(Reverse((new_cost, neighbour)));
+                }
+            }
+        }
+        costs
+    }
+
+    fn render(&self, path: &[Point]) -> String {
+        let mut grid = vec![vec!['.'; self.heights[0].len()]; self.heights.len()];
+        for step in path.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            grid[from.0][from.1] = match (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64)
+            {
+                (-1, 0) => '^',
+                (1, 0) => 'v',
+                (0, -1) => '<',
+                (0, 1) => '>',
+                (-1, 1) | (1, -1) => '/',
+                _ => '\\',
+            };
+        }
+        if let Some(last) = path.last() {
+            grid[last.0][last.1] = 'E';
+        }
+        grid.into_iter()
+            .map(|row| row.into_iter().collect::<String>() + "\n")
+            .collect()
+    }
+}
+
+fn heuristic(node: Point, goal: Point, diagonal: bool) -> usize {
+    let (rows, columns) = (goal.0.abs_diff(node.0), goal.1.abs_diff(node.1));
+    if diagonal {
+        rows.max(columns)
+    } else {
+        rows + columns
+    }
+}
+
+fn nearest_lowest(input: &Input, rules: &dyn Rules) -> Option<usize> {
+    let costs = input.costs_to(rules, input.end);
+    input
+        .heights
+        .iter()
+        .flatten()
+        .zip(costs.iter().flatten())
+        .filter(|(height, _)| **height == b'a')
+        .filter_map(|(_, cost)| *cost)
+        .min()
+}
+
+fn steps(route: Option<(Vec<Point>, usize)>) -> io::Result<usize> {
+    match route {
+        Some((_, cost)) => Ok(cost),
+        None => error("No route to the summit"),
+    }
 }
 
 pub fn part_1() -> io::Result<usize> {
-    let mut input = Input::try_from(FileLines::new(INPUT_FILE)?)?;
-    Ok(do_part_1(&mut input))
+    let input = Input::try_from(FileLines::new(INPUT_FILE)?)?;
+    do_part_1(&input)
 }
 
-fn _part_1_sample() -> io::Result<usize> {
-    let mut input = Input::try_from(FileLines::new(_INPUT_FILE_SAMPLE)?)?;
-    Ok(do_part_1(&mut input))
+pub fn _part_1_sample() -> io::Result<usize> {
+    let input = Input::try_from(FileLines::new(_INPUT_FILE_SAMPLE)?)?;
+    do_part_1(&input)
 }
 
-fn do_part_1(input: &mut Input) -> usize {
-    let start = find_point(&input.map, b'S').unwrap();
-    let end = find_point(&input.map, b'E').unwrap();
-    a_star(start, end, heuristic, &mut input.map).unwrap()
+fn do_part_1(input: &Input) -> io::Result<usize> {
+    steps(input.a_star(&Standard, input.start, input.end))
 }
 
 pub fn part_2() -> io::Result<usize> {
-    let mut input = Input::try_from(FileLines::new(INPUT_FILE)?)?;
-    Ok(do_part_2(&mut input))
+    let input = Input::try_from(FileLines::new(INPUT_FILE)?)?;
+    do_part_2(&input)
 }
 
-fn _part_2_sample() -> io::Result<usize> {
-    let mut input = Input::try_from(FileLines::new(_INPUT_FILE_SAMPLE)?)?;
-    Ok(do_part_2(&mut input))
+pub fn _part_2_sample() -> io::Result<usize> {
+    let input = Input::try_from(FileLines::new(_INPUT_FILE_SAMPLE)?)?;
+    do_part_2(&input)
 }
 
-fn do_part_2(input: &mut Input) -> usize {
-    let mut start_points = find_all_at_elevation(&input.map, b'a');
-    let end = find_point(&input.map, b'E').unwrap();
-
-    start_points.push(find_point(&input.map, b'S').unwrap());
-    let shortest = start_points
-        .into_iter()
-        .flat_map(
-            |start| match a_star(start, end, heuristic, &mut input.map) {
-                Some(distance) => vec![distance],
-                None => vec![],
-            },
-        )
-        .min()
-        .unwrap();
-    shortest
+fn do_part_2(input: &Input) -> io::Result<usize> {
+    match nearest_lowest(input, &Standard) {
+        Some(steps) => Ok(steps),
+        None => error("No route from any lowest point"),
+    }
 }
 
-fn find_point(map: &Map, marker: u8) -> Option<Point> {
-    for row in 0..map.len() {
-        for column in 0..map[0].len() {
-            if map[row][column].elevation == marker {
-                return Some((row, column));
-            }
-        }
-    }
-    None
+fn load(args: &[&str]) -> io::Result<(Input, Box<dyn Rules>)> {
+    let (options, file) = match args.iter().position(|a| *a == "--") {
+        Some(i) => (&args[..i], &args[i + 1..]),
+        None => (args, &[][..]),
+    };
+    let input = Input::try_from(FileLines::new(file_arg(file, INPUT_FILE))?)?;
+    let rules = match options {
+        [] => Box::new(Standard),
+        [spec] => rules_arg(spec)?,
+        _ => error("Expected one comma-separated list of rules")?,
+    };
+    Ok((input, rules))
 }
 
-fn find_all_at_elevation(map: &Map, marker: u8) -> Vec<Point> {
-    let mut result = vec![];
-    for row in 0..map.len() {
-        for column in 0..map[0].len() {
-            if map[row][column].elevation == marker {
-                result.push((row, column));
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["route", rest @ ..] => {
+            let (input, rules) = load(rest)?;
+            match input.a_star(rules.as_ref(), input.start, input.end) {
+                Some((path, cost)) => println!(
+                    "{}{}: {} steps, cost {}",
+                    input.render(&path),
+                    rules.name(),
+                    path.len() - 1,
+                    cost
+                ),
+                None => error("No route to the summit")?,
             }
         }
+        ["nearest", rest @ ..] => {
+            let (input, rules) = load(rest)?;
+            match nearest_lowest(&input, rules.as_ref()) {
+                Some(cost) => println!("{}: cost {}", rules.name(), cost),
+                None => error("No route from any lowest point")?,
+            }
+        }
+        _ => error("Usage: 12 (route|nearest) [rules] [-- file]; rules is a comma-separated list of standard, descent=<n>, steep=<n> and diagonal")?,
     }
-    result
+    Ok(())
 }
 
-fn find_neighbours(point: Point, map: &Map) -> Vec<Point> {
-    let mut result = vec![];
-    let (row, column) = point;
-    let e = map[row][column].elevation;
-    if row > 0 && map[row - 1][column].elevation <= e + 1 {
-        result.push((row - 1, column));
+#[cfg(test)]
+mod tests {
+    use super::hiker::*;
+    use super::{
+        _part_1_sample, _part_2_sample, part_1, part_2, FileLines, Input, _INPUT_FILE_SAMPLE,
+    };
+
+    fn sample() -> Input {
+        Input::try_from(FileLines::new(_INPUT_FILE_SAMPLE).unwrap()).unwrap()
     }
-    if row < map.len() - 1 && map[row + 1][column].elevation <= e + 1 {
-        result.push((row + 1, column));
+
+    #[test]
+    fn test_route() {
+        let input = sample();
+        let (path, cost) = input.a_star(&Standard, input.start, input.end).unwrap();
+        assert_eq!((32, 31), (path.len(), cost));
+        assert_eq!(Some(&(0, 0)), path.first());
+        assert_eq!(Some(&(2, 5)), path.last());
+        let rendered = input.render(&path);
+        assert_eq!(5, rendered.lines().count());
+        assert_eq!(1, rendered.matches('E').count());
+        assert_eq!(31, rendered.chars().filter(|c| "<>^v".contains(*c)).count());
     }
-    if column > 0 && map[row][column - 1].elevation <= e + 1 {
-        result.push((row, column - 1));
+
+    #[test]
+    fn test_costs_to() {
+        let input = sample();
+        let costs = input.costs_to(&Standard, input.end);
+        assert_eq!(Some(31), costs[0][0]);
+        assert_eq!(Some(0), costs[2][5]);
+        assert_eq!(Some(29), costs[4][0]);
     }
-    if column < map[0].len() - 1 && map[row][column + 1].elevation <= e + 1 {
-        result.push((row, column + 1));
+
+    #[test]
+    fn test_rules() {
+        let input = sample();
+        for spec in [
+            "standard",
+            "descent=1",
+            "steep=3",
+            "diagonal",
+            "descent=0,steep=2",
+        ] {
+            let rules = rules_arg(spec).unwrap();
+            let (path, cost) = input
+                .a_star(rules.as_ref(), input.start, input.end)
+                .unwrap();
+            let mut walked = 0;
+            for step in path.windows(2) {
+                let (from, to) = (input.height(step[0]), input.height(step[1]));
+                assert!(input
+                    .neighbours(step[0], rules.diagonal())
+                    .contains(&step[1]));
+                assert!(rules.can_step(from, to));
+                walked += rules.cost(from, to);
+            }
+            assert_eq!(cost, walked, "{}", spec);
+            let costs = input.costs_to(rules.as_ref(), input.end);
+            assert_eq!(Some(cost), costs[input.start.0][input.start.1], "{}", spec);
+        }
+        assert!(rules_arg("fly").is_err());
     }
-    result
-}
 
-fn heuristic(node: Point, goal: Point) -> usize {
-    goal.0.abs_diff(node.0) + goal.1.abs_diff(node.1)
-}
+    struct LevelFree;
 
-fn a_star(start: Point, goal: Point, h: fn(Point, Point) -> usize, map: &mut Map) -> Option<usize> {
-    let mut open_set: HashSet<Point> = HashSet::new();
-    let mut came_from: HashMap<Point, Point> = HashMap::new();
-    let mut current: Point;
-    open_set.insert(start);
-    let mut start_vertex = &mut map[start.0][start.1];
-    start_vertex.elevation = b'a';
-    start_vertex.f_score = h(start, goal);
-    start_vertex.g_score = 0;
-    let mut end_vertex = &mut map[goal.0][goal.1];
-    end_vertex.elevation = b'z';
-
-    while !open_set.is_empty() {
-        current = *open_set
-            .iter()
-            .min_by_key(|(r, c)| map[*r][*c].f_score)
-            .unwrap();
-        let ref mut current_vertex = map[current.0][current.1];
-        if current == goal {
-            return Some(current_vertex.g_score);
+    impl Rules for LevelFree {
+        fn name(&self) -> String {
+            String::from("level-free")
         }
-        open_set.remove(&current);
-
-        for neighbour in find_neighbours(current, map) {
-            let new_score = map[current.0][current.1].g_score + 1;
-            let mut neighbour_vertex = &mut map[neighbour.0][neighbour.1];
-            if new_score < neighbour_vertex.g_score {
-                came_from.insert(neighbour, current);
-                neighbour_vertex.g_score = new_score;
-                neighbour_vertex.f_score = new_score + h(neighbour, goal);
-
-                if !open_set.iter().any(|&x| x == neighbour) {
-                    open_set.insert(neighbour);
-                }
-            }
+
+        fn can_step(&self, _from: u8, _to: u8) -> bool {
+            true
+        }
+
+        fn cost(&self, from: u8, to: u8) -> usize {
+            usize::from(from != to)
         }
     }
-    None
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    #[test]
+    fn test_zero_cost_steps() {
+        let heights = ["bcbab", "acaac", "acbcb"];
+        let input = Input {
+            heights: heights.iter().map(|r| r.bytes().collect()).collect(),
+            start: (0, 0),
+            end: (0, 4),
+        };
+        assert_eq!(0, cheapest_step(&LevelFree));
+        let (_, cost) = input.a_star(&LevelFree, input.start, input.end).unwrap();
+        assert_eq!(3, cost);
+        assert_eq!(Some(3), input.costs_to(&LevelFree, input.end)[0][0]);
+    }
 
     #[test]
     fn test_part_1() {
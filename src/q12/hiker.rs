@@ -0,0 +1,126 @@
+use crate::parser::*;
+use std::io;
+
+pub trait Rules {
+    fn name(&self) -> String;
+    fn can_step(&self, from: u8, to: u8) -> bool;
+
+    // May be zero; A* scales its distance heuristic by the cheapest allowed step.
+    fn cost(&self, _from: u8, _to: u8) -> usize {
+        1
+    }
+
+    fn diagonal(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug)]
+pub struct Standard;
+
+impl Rules for Standard {
+    fn name(&self) -> String {
+        String::from("standard")
+    }
+
+    fn can_step(&self, from: u8, to: u8) -> bool {
+        to <= from + 1
+    }
+}
+
+pub struct MaxDescent {
+    pub inner: Box<dyn Rules>,
+    pub limit: u8,
+}
+
+impl Rules for MaxDescent {
+    fn name(&self) -> String {
+        format!("{},descent={}", self.inner.name(), self.limit)
+    }
+
+    fn can_step(&self, from: u8, to: u8) -> bool {
+        self.inner.can_step(from, to) && from.saturating_sub(to) <= self.limit
+    }
+
+    fn cost(&self, from: u8, to: u8) -> usize {
+        self.inner.cost(from, to)
+    }
+
+    fn diagonal(&self) -> bool {
+        self.inner.diagonal()
+    }
+}
+
+pub struct ElevationCost {
+    pub inner: Box<dyn Rules>,
+    pub per_level: usize,
+}
+
+impl Rules for ElevationCost {
+    fn name(&self) -> String {
+        format!("{},steep={}", self.inner.name(), self.per_level)
+    }
+
+    fn can_step(&self, from: u8, to: u8) -> bool {
+        self.inner.can_step(from, to)
+    }
+
+    fn cost(&self, from: u8, to: u8) -> usize {
+        self.inner.cost(from, to) + self.per_level * from.abs_diff(to) as usize
+    }
+
+    fn diagonal(&self) -> bool {
+        self.inner.diagonal()
+    }
+}
+
+pub struct Diagonal {
+    pub inner: Box<dyn Rules>,
+}
+
+impl Rules for Diagonal {
+    fn name(&self) -> String {
+        format!("{},diagonal", self.inner.name())
+    }
+
+    fn can_step(&self, from: u8, to: u8) -> bool {
+        self.inner.can_step(from, to)
+    }
+
+    fn cost(&self, from: u8, to: u8) -> usize {
+        self.inner.cost(from, to)
+    }
+
+    fn diagonal(&self) -> bool {
+        true
+    }
+}
+
+pub fn cheapest_step(rules: &dyn Rules) -> usize {
+    (b'a'..=b'z')
+        .flat_map(|from| (b'a'..=b'z').map(move |to| (from, to)))
+        .filter(|(from, to)| rules.can_step(*from, *to))
+        .map(|(from, to)| rules.cost(from, to))
+        .min()
+        .unwrap_or(0)
+}
+
+pub fn rules_arg(spec: &str) -> io::Result<Box<dyn Rules>> {
+    let mut rules: Box<dyn Rules> = Box::new(Standard);
+    for part in spec.split(',') {
+        rules = match part.split_once('=') {
+            None if part == "standard" => rules,
+            None if part == "diagonal" => Box::new(Diagonal { inner: rules }),
+            Some(("descent", limit)) => Box::new(MaxDescent {
+                inner: rules,
+                limit: arg(limit)?,
+            }),
+            Some(("steep", per_level)) => Box::new(ElevationCost {
+                inner: rules,
+                per_level: arg(per_level)?,
+            }),
+            _ => error(&format!("Unknown rule: {}", part))?,
+        };
+    }
+    Ok(rules)
+}
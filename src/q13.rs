@@ -1,208 +1,276 @@
+mod json;
+mod order;
+mod packet;
+
 use crate::parser::*;
-use std::cmp;
+use json::Json;
+use order::*;
+use packet::*;
+use std::fs;
 use std::io;
 
 const INPUT_FILE: &str = "input/input13.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input13_sample.txt";
 
-#[derive(Debug)]
-struct Packet {
-    data: PacketData,
-}
-
-impl From<&str> for Packet {
-    fn from(s: &str) -> Self {
-        let data = &s[1..s.len() - 1];
-        let mut stack: Vec<Vec<PacketData>> = Vec::new();
-        let mut num_stack = String::new();
-        stack.push(Vec::new());
-        for c in data.chars() {
-            match c {
-                '[' => {
-                    stack.push(Vec::new());
-                }
-                ',' => {
-                    if !num_stack.is_empty() {
-                        let number = PacketData::Int(num_stack.parse().unwrap());
-                        stack.last_mut().unwrap().push(number);
-                        num_stack.clear();
-                    }
-                }
-                ']' => {
-                    if !num_stack.is_empty() {
-                        let number = PacketData::Int(num_stack.parse().unwrap());
-                        stack.last_mut().unwrap().push(number);
-                        num_stack.clear();
-                    }
-                    let packet = PacketData::List(stack.pop().unwrap());
-                    stack.last_mut().unwrap().push(packet);
-                }
-                x if x.is_ascii_digit() => num_stack.push(x),
-                _ => {}
-            }
-        }
-        if !num_stack.is_empty() {
-            let number = PacketData::Int(num_stack.parse().unwrap());
-            stack.last_mut().unwrap().push(number);
-            num_stack.clear();
-        }
-        Packet {
-            data: PacketData::List(stack.pop().unwrap()),
+fn read_packets(input_file: &str) -> io::Result<Vec<PacketData>> {
+    let mut packets = vec![];
+    for (i, line) in FileLines::new(input_file)?.enumerate() {
+        match line.parse() {
+            _ if line.is_empty() => continue,
+            Ok(data @ PacketData::List(_)) => packets.push(data),
+            Ok(_) => error(&format!("Line {}: packet is not a list: {}", i + 1, line))?,
+            Err(e) => error(&format!("Line {}: {} in {}", i + 1, e, line))?,
         }
     }
+    Ok(packets)
 }
 
-#[derive(Debug)]
-enum PacketData {
-    Int(u32),
-    List(Vec<PacketData>),
+fn read_pairs(input_file: &str) -> io::Result<Vec<(PacketData, PacketData)>> {
+    let packets = read_packets(input_file)?;
+    if packets.len() % 2 != 0 {
+        return error("Packets do not form pairs");
+    }
+    let mut pairs = vec![];
+    let mut packets = packets.into_iter();
+    while let (Some(left), Some(right)) = (packets.next(), packets.next()) {
+        pairs.push((left, right));
+    }
+    Ok(pairs)
 }
 
-impl PartialEq for PacketData {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (PacketData::Int(l), PacketData::Int(r)) => l == r,
-            (PacketData::List(l), PacketData::List(r)) => {
-                l.len() == r.len() && l.iter().zip(r.iter()).all(|(x, y)| x == y)
-            }
-            (l, PacketData::Int(r)) => l == &PacketData::List(vec![PacketData::Int(*r)]),
-            (PacketData::Int(l), r) => &PacketData::List(vec![PacketData::Int(*l)]) == r,
-        }
-    }
+pub fn part_1() -> io::Result<usize> {
+    do_part_1(INPUT_FILE)
 }
 
-impl Eq for PacketData {}
+pub fn _part_1_sample() -> io::Result<usize> {
+    do_part_1(_INPUT_FILE_SAMPLE)
+}
 
-impl PartialOrd for PacketData {
-    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        match (self, other) {
-            (PacketData::Int(l), PacketData::Int(r)) => {
-                return l.partial_cmp(r);
-            }
-            (PacketData::List(l), PacketData::List(r)) => {
-                for (a, b) in l.iter().zip(r.iter()) {
-                    let cmp = a.partial_cmp(b);
-                    if cmp != Some(cmp::Ordering::Equal) {
-                        return cmp;
-                    }
-                }
-                if l.len() < r.len() {
-                    return Some(cmp::Ordering::Less);
-                } else if l.len() > r.len() {
-                    return Some(cmp::Ordering::Greater);
-                } else {
-                    return Some(cmp::Ordering::Equal);
-                }
-            }
-            (l, PacketData::Int(r)) => {
-                return l.partial_cmp(&PacketData::List(vec![PacketData::Int(*r)]));
-            }
-            (PacketData::Int(l), r) => {
-                return PacketData::List(vec![PacketData::Int(*l)]).partial_cmp(r);
-            }
+fn do_part_1(input_file: &str) -> io::Result<usize> {
+    let mut total = 0;
+    for (i, (left, right)) in read_pairs(input_file)?.iter().enumerate() {
+        if left <= right {
+            total += i + 1;
         }
     }
+    Ok(total)
 }
 
-impl Ord for PacketData {
-    fn cmp(&self, other: &Self) -> cmp::Ordering {
-        self.partial_cmp(other).unwrap()
-    }
+pub fn part_2() -> io::Result<usize> {
+    do_part_2(INPUT_FILE)
 }
 
-#[derive(Debug)]
-struct PacketPair {
-    left: Packet,
-    right: Packet,
+pub fn _part_2_sample() -> io::Result<usize> {
+    do_part_2(_INPUT_FILE_SAMPLE)
 }
 
-impl TryFrom<&mut FileLines> for PacketPair {
-    type Error = io::Error;
-
-    fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
-        let left = Packet::from(lines.next_result()?.as_str());
-        let right = Packet::from(lines.next_result()?.as_str());
-        lines.next();
-        Ok(PacketPair { left, right })
+fn do_part_2(input_file: &str) -> io::Result<usize> {
+    let mut query = DividerQuery::new(vec!["[[2]]".parse()?, "[[6]]".parse()?]);
+    for packet in read_packets(input_file)? {
+        query.observe(&packet);
     }
+    Ok(query.ranks().iter().product())
 }
 
-impl Iterator for FileInput<FileLines, PacketPair> {
-    type Item = PacketPair;
-
-    fn next(&mut self) -> Option<PacketPair> {
-        parse_from(&mut self.source)
-    }
+fn dividers_and_file<'a>(args: &[&'a str]) -> io::Result<(Vec<PacketData>, &'a str)> {
+    let (dividers, file) = match args.iter().position(|a| *a == "--") {
+        Some(i) => (&args[..i], &args[i + 1..]),
+        None => (args, &[][..]),
+    };
+    let dividers = dividers
+        .iter()
+        .map(|d| d.parse().map_err(io::Error::from))
+        .collect::<io::Result<_>>()?;
+    Ok((dividers, file_arg(file, INPUT_FILE)))
 }
 
-impl TryFrom<&mut FileLines> for Packet {
-    type Error = io::Error;
-
-    fn try_from(lines: &mut FileLines) -> Result<Self, Self::Error> {
-        match lines.next_result()?.as_str() {
-            "" => Packet::try_from(lines),
-            x => Ok(Packet::from(x)),
+fn check(input_file: &str) -> io::Result<usize> {
+    let mut count = 0;
+    for (i, line) in FileLines::new(input_file)?.enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        match line.parse::<PacketData>() {
+            Ok(data) if data.to_string() == line => count += 1,
+            Ok(data) => error(&format!("Line {}: not canonical, expected {}", i + 1, data))?,
+            Err(e) => error(&format!("Line {}: {}", i + 1, e))?,
         }
     }
+    Ok(count)
 }
 
-impl Iterator for FileInput<FileLines, Packet> {
-    type Item = Packet;
-
-    fn next(&mut self) -> Option<Packet> {
-        parse_from(&mut self.source)
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        ["check", rest @ ..] => {
+            println!("{} packets OK", check(file_arg(rest, INPUT_FILE))?);
+        }
+        ["to-json", rest @ ..] => {
+            let packets = read_packets(file_arg(rest, INPUT_FILE))?;
+            println!("{}", Json::Array(packets.iter().map(Json::from).collect()));
+        }
+        ["from-json", file] => {
+            let json: Json = fs::read_to_string(file)?.parse()?;
+            match json {
+                Json::Array(values) => {
+                    for value in &values {
+                        println!("{}", PacketData::try_from(value)?);
+                    }
+                }
+                _ => error("Expected a JSON array of packets")?,
+            }
+        }
+        ["dividers", rest @ ..] => {
+            let (dividers, file) = dividers_and_file(rest)?;
+            let mut query = DividerQuery::new(dividers);
+            for packet in read_packets(file)? {
+                query.observe(&packet);
+            }
+            let ranks = query.ranks();
+            for (divider, rank) in query.dividers.iter().zip(&ranks) {
+                println!("{} {}", rank, divider);
+            }
+            println!("Product: {}", ranks.iter().product::<usize>());
+        }
+        ["sorted", rest @ ..] => {
+            let (dividers, file) = dividers_and_file(rest)?;
+            let packets = read_packets(file)?;
+            let mut query = DividerQuery::new(dividers.clone());
+            for packet in &packets {
+                query.observe(packet);
+            }
+            let ranks = query.ranks();
+            for (i, packet) in sorted(packets, &dividers).iter().enumerate() {
+                let marker = if ranks.contains(&(i + 1)) { '*' } else { ' ' };
+                println!("{:>4}{} {}", i + 1, marker, packet);
+            }
+        }
+        _ => error("Usage: 13 (check [file]|to-json [file]|from-json <file>|dividers <packet>... [-- file]|sorted [packet...] [-- file])")?,
     }
+    Ok(())
 }
 
-pub fn part_1() -> io::Result<usize> {
-    do_part_1(INPUT_FILE)
-}
+#[cfg(test)]
+mod tests {
+    use super::json::Json;
+    use super::order::*;
+    use super::packet::*;
+    use super::{
+        _part_1_sample, _part_2_sample, check, do_part_1, part_1, part_2, read_packets,
+        _INPUT_FILE_SAMPLE,
+    };
+    use std::cmp;
+    use std::env;
+    use std::fs;
 
-fn _part_1_sample() -> io::Result<usize> {
-    do_part_1(_INPUT_FILE_SAMPLE)
-}
+    fn random_packet(seed: &mut u64, depth: u32) -> PacketData {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        if depth == 0 || seed.is_multiple_of(3) {
+            return PacketData::Int((*seed >> 8) as u32 % 20);
+        }
+        let length = (*seed >> 16) % 5;
+        PacketData::List(
+            (0..length)
+                .map(|_| random_packet(seed, depth - 1))
+                .collect(),
+        )
+    }
 
-fn do_part_1(input_file: &str) -> io::Result<usize> {
-    let packet_pairs = parse_lines::<PacketPair>(input_file)?;
-    let mut total = 0;
-    for (i, packet_pair) in packet_pairs.enumerate() {
-        if packet_pair.left.data <= packet_pair.right.data {
-            total += i + 1;
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(16, check(_INPUT_FILE_SAMPLE).unwrap());
+        let mut seed = 0x2545f4914f6cdd1d;
+        for _ in 0..200 {
+            let packet = random_packet(&mut seed, 4);
+            let text = packet.to_string();
+            let parsed: PacketData = text.parse().unwrap();
+            assert_eq!(text, parsed.to_string());
+            let json: Json = text.parse().unwrap();
+            assert_eq!(Json::from(&packet), json);
+            assert_eq!(text, PacketData::try_from(&json).unwrap().to_string());
         }
     }
-    Ok(total)
-}
 
-pub fn part_2() -> io::Result<usize> {
-    do_part_2(INPUT_FILE)
-}
+    #[test]
+    fn test_parse_errors() {
+        let position = |s: &str| s.parse::<PacketData>().unwrap_err().position;
+        assert_eq!(0, position(""));
+        assert_eq!(3, position("[1,]"));
+        assert_eq!(2, position("[1 ,2]"));
+        assert_eq!(4, position("[[1]"));
+        assert_eq!(3, position("[1]]"));
+        assert_eq!(1, position("[99999999999]"));
+        assert_eq!(1, position("[-1]"));
+        assert_eq!(
+            "Column 4: expected an integer or [",
+            "[1,]".parse::<PacketData>().unwrap_err().to_string()
+        );
+    }
 
-fn _part_2_sample() -> io::Result<usize> {
-    do_part_2(_INPUT_FILE_SAMPLE)
-}
+    #[test]
+    fn test_malformed_file() {
+        let file = env::temp_dir().join(format!("q13_malformed_{}.txt", std::process::id()));
+        fs::write(&file, "[1,2]\n[3]\n\n[1,x]\n[4]\n").unwrap();
+        let e = read_packets(file.to_str().unwrap())
+            .unwrap_err()
+            .to_string();
+        assert_eq!("Line 4: Column 4: expected an integer or [ in [1,x]", e);
+        assert!(do_part_1(file.to_str().unwrap()).is_err());
+        fs::write(&file, "[1]\n3\n").unwrap();
+        assert!(read_packets(file.to_str().unwrap()).is_err());
+        fs::write(&file, "[1]\n[2]\n\n[3]\n").unwrap();
+        assert!(do_part_1(file.to_str().unwrap()).is_err());
+        fs::remove_file(&file).unwrap();
+    }
 
-fn do_part_2(input_file: &str) -> io::Result<usize> {
-    let mut packets: Vec<PacketData> = parse_lines::<Packet>(input_file)?.map(|p| p.data).collect();
-    let mut total = 1;
-    let divider_1 = PacketData::List(vec![PacketData::Int(2)]);
-    let divider_2 = PacketData::List(vec![PacketData::Int(6)]);
-    packets.push(divider_1);
-    packets.push(divider_2);
-
-    packets.sort();
-    let divider_1 = PacketData::List(vec![PacketData::Int(2)]);
-    let divider_2 = PacketData::List(vec![PacketData::Int(6)]);
-    for (i, packet) in packets.iter().enumerate() {
-        if packet == &divider_1 || packet == &divider_2 {
-            total *= i + 1;
-        }
+    #[test]
+    fn test_json() {
+        let json: Json = r#" { "packets": [ [1, [2, 3.0]], [] ], "name": "a\"b\u0041" } "#
+            .parse()
+            .unwrap();
+        assert_eq!(
+            r#"{"name":"a\"bA","packets":[[1,[2,3]],[]]}"#,
+            json.to_string()
+        );
+        let packets = match &json {
+            Json::Object(members) => &members["packets"],
+            _ => panic!("Expected an object"),
+        };
+        let data = PacketData::try_from(packets).unwrap();
+        assert_eq!("[[1,[2,3]],[]]", data.to_string());
+        assert!(PacketData::try_from(&Json::Number(1.5)).is_err());
+        assert!(PacketData::try_from(&Json::Null).is_err());
+        assert_eq!(5, "[1, 2".parse::<Json>().unwrap_err().position);
     }
-    Ok(total)
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    #[test]
+    fn test_dividers() {
+        let packets = read_packets(_INPUT_FILE_SAMPLE).unwrap();
+        let dividers: Vec<PacketData> = ["[[6]]", "[[2]]", "[]", "[[2]]", "[1,1,3,1,1]", "9"]
+            .iter()
+            .map(|d| d.parse().unwrap())
+            .collect();
+        let mut query = DividerQuery::new(dividers.clone());
+        for packet in &packets {
+            query.observe(packet);
+        }
+        let ranks = query.ranks();
+        assert_eq!(vec![17, 12, 2, 13, 6, 22], ranks);
+        let sorted = sorted(packets, &dividers);
+        assert!(sorted.windows(2).all(|w| compare(&w[0], &w[1]).is_le()));
+        for (divider, rank) in dividers.iter().zip(&ranks) {
+            assert_eq!(divider.to_string(), sorted[rank - 1].to_string());
+        }
+        assert_eq!(
+            cmp::Ordering::Greater,
+            compare(&"[[1],4]".parse().unwrap(), &"[1,1,3]".parse().unwrap())
+        );
+        assert_eq!(
+            cmp::Ordering::Equal,
+            compare(&"[[[3]]]".parse().unwrap(), &"3".parse().unwrap())
+        );
+    }
 
     #[test]
     fn test_part_1() {
@@ -0,0 +1,224 @@
+use super::packet::ParseError;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct JsonParser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl JsonParser<'_> {
+    fn fail<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError {
+            position: self.position,
+            message: String::from(message),
+        })
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        self.text[self.position..].chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(next) if next == c => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => self.fail(&format!("expected {}", c)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, ParseError> {
+        match self.peek() {
+            Some('[') => {
+                self.position += 1;
+                let mut values = vec![];
+                if self.peek() == Some(']') {
+                    self.position += 1;
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    match self.peek() {
+                        Some(',') => self.position += 1,
+                        Some(']') => {
+                            self.position += 1;
+                            return Ok(Json::Array(values));
+                        }
+                        _ => return self.fail("expected , or ]"),
+                    }
+                }
+            }
+            Some('{') => {
+                self.position += 1;
+                let mut members = BTreeMap::new();
+                if self.peek() == Some('}') {
+                    self.position += 1;
+                    return Ok(Json::Object(members));
+                }
+                loop {
+                    if self.peek() != Some('"') {
+                        return self.fail("expected a key");
+                    }
+                    let key = self.string()?;
+                    self.expect(':')?;
+                    members.insert(key, self.value()?);
+                    match self.peek() {
+                        Some(',') => self.position += 1,
+                        Some('}') => {
+                            self.position += 1;
+                            return Ok(Json::Object(members));
+                        }
+                        _ => return self.fail("expected , or }"),
+                    }
+                }
+            }
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => {
+                for (word, value) in [
+                    ("null", Json::Null),
+                    ("true", Json::Bool(true)),
+                    ("false", Json::Bool(false)),
+                ] {
+                    if self.text[self.position..].starts_with(word) {
+                        self.position += word.len();
+                        return Ok(value);
+                    }
+                }
+                self.fail("expected a value")
+            }
+            None => self.fail("unexpected end of input"),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, ParseError> {
+        let rest = &self.text[self.position..];
+        let length = rest
+            .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+            .unwrap_or(rest.len());
+        match rest[..length].parse() {
+            Ok(n) => {
+                self.position += length;
+                Ok(Json::Number(n))
+            }
+            Err(_) => self.fail("invalid number"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.position += 1;
+        let mut s = String::new();
+        let mut chars = self.text[self.position..].char_indices();
+        let start = self.position;
+        while let Some((i, c)) = chars.next() {
+            self.position = start + i;
+            match c {
+                '"' => {
+                    self.position += 1;
+                    return Ok(s);
+                }
+                '\\' => s.push(match chars.next() {
+                    Some((_, '"')) => '"',
+                    Some((_, '\\')) => '\\',
+                    Some((_, '/')) => '/',
+                    Some((_, 'b')) => '\u{8}',
+                    Some((_, 'f')) => '\u{c}',
+                    Some((_, 'n')) => '\n',
+                    Some((_, 'r')) => '\r',
+                    Some((_, 't')) => '\t',
+                    Some((_, 'u')) => {
+                        let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                        match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                            Some(c) if hex.len() == 4 => c,
+                            _ => return self.fail("invalid unicode escape"),
+                        }
+                    }
+                    _ => return self.fail("invalid escape"),
+                }),
+                c if (c as u32) < 0x20 => return self.fail("control character in string"),
+                c => s.push(c),
+            }
+        }
+        self.position = self.text.len();
+        self.fail("unterminated string")
+    }
+}
+
+impl FromStr for Json {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = JsonParser {
+            text: s,
+            position: 0,
+        };
+        let value = parser.value()?;
+        match parser.peek() {
+            None => Ok(value),
+            Some(_) => parser.fail("trailing characters"),
+        }
+    }
+}
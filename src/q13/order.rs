@@ -0,0 +1,46 @@
+use super::packet::*;
+use std::cmp;
+
+pub struct DividerQuery {
+    pub dividers: Vec<PacketData>,
+    below: Vec<usize>,
+}
+
+impl DividerQuery {
+    pub fn new(dividers: Vec<PacketData>) -> Self {
+        let below = dividers
+            .iter()
+            .enumerate()
+            .map(|(i, divider)| {
+                dividers
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, other)| match compare(other, divider) {
+                        cmp::Ordering::Less => true,
+                        cmp::Ordering::Equal => *j < i,
+                        cmp::Ordering::Greater => false,
+                    })
+                    .count()
+            })
+            .collect();
+        DividerQuery { dividers, below }
+    }
+
+    pub fn observe(&mut self, packet: &PacketData) {
+        for (divider, below) in self.dividers.iter().zip(self.below.iter_mut()) {
+            if compare(packet, divider) != cmp::Ordering::Greater {
+                *below += 1;
+            }
+        }
+    }
+
+    pub fn ranks(&self) -> Vec<usize> {
+        self.below.iter().map(|below| below + 1).collect()
+    }
+}
+
+pub fn sorted(mut packets: Vec<PacketData>, dividers: &[PacketData]) -> Vec<PacketData> {
+    packets.extend(dividers.iter().cloned());
+    packets.sort_by(compare);
+    packets
+}
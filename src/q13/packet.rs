@@ -0,0 +1,194 @@
+use super::json::Json;
+use crate::parser::*;
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Column {}: {}", self.position + 1, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PacketData {
+    Int(u32),
+    List(Vec<PacketData>),
+}
+
+impl PartialEq for PacketData {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl Eq for PacketData {}
+
+impl PartialOrd for PacketData {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PacketData {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        compare(self, other)
+    }
+}
+
+pub fn compare(left: &PacketData, right: &PacketData) -> cmp::Ordering {
+    match (left, right) {
+        (PacketData::Int(l), PacketData::Int(r)) => l.cmp(r),
+        (PacketData::List(l), PacketData::List(r)) => {
+            for (a, b) in l.iter().zip(r) {
+                match compare(a, b) {
+                    cmp::Ordering::Equal => continue,
+                    ordering => return ordering,
+                }
+            }
+            l.len().cmp(&r.len())
+        }
+        (PacketData::List(l), PacketData::Int(_)) => match l.first() {
+            None => cmp::Ordering::Less,
+            Some(first) => compare(first, right).then(l.len().cmp(&1)),
+        },
+        (PacketData::Int(_), PacketData::List(_)) => compare(right, left).reverse(),
+    }
+}
+
+impl fmt::Display for PacketData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PacketData::Int(n) => write!(f, "{}", n),
+            PacketData::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+struct PacketParser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl PacketParser<'_> {
+    fn fail<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError {
+            position: self.position,
+            message: String::from(message),
+        })
+    }
+
+    fn value(&mut self) -> Result<PacketData, ParseError> {
+        match self.bytes.get(self.position) {
+            Some(b'[') => {
+                self.position += 1;
+                let mut items = vec![];
+                if self.bytes.get(self.position) == Some(&b']') {
+                    self.position += 1;
+                    return Ok(PacketData::List(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    match self.bytes.get(self.position) {
+                        Some(b',') => self.position += 1,
+                        Some(b']') => {
+                            self.position += 1;
+                            return Ok(PacketData::List(items));
+                        }
+                        Some(_) => return self.fail("expected , or ]"),
+                        None => return self.fail("unclosed list"),
+                    }
+                }
+            }
+            Some(b'0'..=b'9') => {
+                let start = self.position;
+                while self
+                    .bytes
+                    .get(self.position)
+                    .is_some_and(u8::is_ascii_digit)
+                {
+                    self.position += 1;
+                }
+                let digits = std::str::from_utf8(&self.bytes[start..self.position]).unwrap();
+                match digits.parse() {
+                    Ok(n) => Ok(PacketData::Int(n)),
+                    Err(_) => {
+                        self.position = start;
+                        self.fail("integer out of range")
+                    }
+                }
+            }
+            Some(_) => self.fail("expected an integer or ["),
+            None => self.fail("unexpected end of packet"),
+        }
+    }
+}
+
+impl FromStr for PacketData {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = PacketParser {
+            bytes: s.as_bytes(),
+            position: 0,
+        };
+        let data = parser.value()?;
+        if parser.position < s.len() {
+            return parser.fail("trailing characters");
+        }
+        Ok(data)
+    }
+}
+
+impl From<&PacketData> for Json {
+    fn from(data: &PacketData) -> Self {
+        match data {
+            PacketData::Int(n) => Json::Number(f64::from(*n)),
+            PacketData::List(items) => Json::Array(items.iter().map(Json::from).collect()),
+        }
+    }
+}
+
+impl TryFrom<&Json> for PacketData {
+    type Error = io::Error;
+
+    fn try_from(json: &Json) -> Result<Self, Self::Error> {
+        match json {
+            Json::Number(n) if n.fract() == 0.0 && (0.0..=f64::from(u32::MAX)).contains(n) => {
+                Ok(PacketData::Int(*n as u32))
+            }
+            Json::Array(values) => Ok(PacketData::List(
+                values
+                    .iter()
+                    .map(PacketData::try_from)
+                    .collect::<io::Result<_>>()?,
+            )),
+            _ => error(&format!("Not a packet: {}", json)),
+        }
+    }
+}
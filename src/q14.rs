@@ -1,4 +1,7 @@
+mod sand;
+
 use crate::parser::*;
+use sand::*;
 use std::collections::HashSet;
 use std::io;
 
@@ -6,175 +9,189 @@ const INPUT_FILE: &str = "input/input14.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input14_sample.txt";
 const SOURCE: Point = (500, 0);
 
-type Point = (u32, u32);
-
 #[derive(Debug)]
 struct Input {
-    grid: HashSet<Point>,
-    abyss_depth: u32,
+    rocks: HashSet<Point>,
 }
 
 impl TryFrom<FileLines> for Input {
     type Error = io::Error;
 
     fn try_from(lines: FileLines) -> Result<Self, Self::Error> {
-        let mut grid = HashSet::new();
+        let mut rocks = HashSet::new();
         for line in lines {
             let mut points = line.split(" -> ");
-            let mut last_point = convert_point(points.next().unwrap());
+            let mut last_point = convert_point(points.next().unwrap_or(""))?;
+            rocks.insert(last_point);
             for point in points {
-                let current_point = convert_point(point);
+                let current_point = convert_point(point)?;
                 match (last_point, current_point) {
                     ((x1, y1), (x2, y2)) if x1 == x2 => {
-                        let a = std::cmp::min(y1, y2);
-                        let b = a + y1.abs_diff(y2);
-                        for y in a..b {
-                            grid.insert((x1, y));
+                        for y in y1.min(y2)..=y1.max(y2) {
+                            rocks.insert((x1, y));
                         }
-                        grid.insert((x1, b));
                     }
                     ((x1, y1), (x2, y2)) if y1 == y2 => {
-                        let a = std::cmp::min(x1, x2);
-                        let b = a + x1.abs_diff(x2);
-                        for x in a..b {
-                            grid.insert((x, y1));
+                        for x in x1.min(x2)..=x1.max(x2) {
+                            rocks.insert((x, y1));
                         }
-                        grid.insert((b, y1));
                     }
                     _ => error("Input does not appear vertical")?,
                 }
                 last_point = current_point;
             }
         }
-        let abyss_depth = grid.iter().map(|p| p.1).max().unwrap();
-        Ok(Input { grid, abyss_depth })
+        Ok(Input { rocks })
     }
 }
 
-pub fn part_1() -> io::Result<u32> {
+pub fn part_1() -> io::Result<usize> {
     let input = Input::try_from(FileLines::new(INPUT_FILE)?)?;
     Ok(do_part_1(input))
 }
 
-fn _part_1_sample() -> io::Result<u32> {
+pub fn _part_1_sample() -> io::Result<usize> {
     let input = Input::try_from(FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_1(input))
 }
 
-fn do_part_1(mut input: Input) -> u32 {
-    let mut grains = 0;
-
-    while let Some(grain) = drop_grain_with_abyss(&input.grid, input.abyss_depth) {
-        input.grid.insert(grain);
-        grains += 1;
-    }
-    grains
+fn do_part_1(input: Input) -> usize {
+    SandSim::new(input.rocks, vec![SOURCE]).run().settled
 }
 
-pub fn part_2() -> io::Result<u32> {
+pub fn part_2() -> io::Result<usize> {
     let input = Input::try_from(FileLines::new(INPUT_FILE)?)?;
     Ok(do_part_2(input))
 }
 
-fn _part_2_sample() -> io::Result<u32> {
+pub fn _part_2_sample() -> io::Result<usize> {
     let input = Input::try_from(FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_2(input))
 }
 
-fn do_part_2(mut input: Input) -> u32 {
-    let mut grains = 0;
-    let floor_depth = input.abyss_depth + 2;
-
-    while let Some(grain) = drop_grain_with_floor(&input.grid, floor_depth) {
-        input.grid.insert(grain);
-        grains += 1;
-    }
-    grains
+fn do_part_2(input: Input) -> usize {
+    SandSim::new(input.rocks, vec![SOURCE])
+        .with_floor(2)
+        .run()
+        .settled
 }
 
-fn convert_point(string: &str) -> (u32, u32) {
-    match string.split_once(",") {
-        Some((x, y)) => (x.parse().unwrap(), y.parse().unwrap()),
-        None => (0, 0),
+fn convert_point(string: &str) -> io::Result<Point> {
+    match string.split_once(',') {
+        Some((x, y)) => Ok((arg(x.trim())?, arg(y.trim())?)),
+        None => error(&format!("Failed to parse point: {}", string)),
     }
 }
 
-fn drop_grain_with_abyss(grid: &HashSet<Point>, abyss_depth: u32) -> Option<Point> {
-    let (mut x, mut y) = SOURCE;
-    while y < abyss_depth {
-        if !grid.contains(&(x, y + 1)) {
-            y += 1;
-        } else if !grid.contains(&(x - 1, y + 1)) {
-            x -= 1;
-            y += 1;
-        } else if !grid.contains(&(x + 1, y + 1)) {
-            x += 1;
-            y += 1;
-        } else {
-            return Some((x, y));
+fn simulator(args: &[&str]) -> io::Result<SandSim> {
+    let (options, file) = match args.iter().position(|a| *a == "--") {
+        Some(i) => (&args[..i], &args[i + 1..]),
+        None => (args, &[][..]),
+    };
+    let input = Input::try_from(FileLines::new(file_arg(file, INPUT_FILE))?)?;
+    let mut sources = vec![];
+    let (mut floor, mut walls) = (None, None);
+    for option in options {
+        match option.split_once('=') {
+            Some(("source", point)) => sources.push(convert_point(point)?),
+            Some(("floor", offset)) => floor = Some(arg(offset)?),
+            Some(("walls", range)) => walls = Some(convert_point(range)?),
+            _ => error(&format!("Unknown option: {}", option))?,
         }
     }
-    None
-}
-
-fn drop_grain_with_floor(grid: &HashSet<Point>, floor_depth: u32) -> Option<Point> {
-    let (mut x, mut y) = SOURCE;
-    if grid.contains(&SOURCE) {
-        return None;
+    if sources.is_empty() {
+        sources.push(SOURCE);
     }
-    loop {
-        if !grid.contains(&(x, y + 1)) && y < floor_depth - 1 {
-            y += 1;
-        } else if !grid.contains(&(x - 1, y + 1)) && y < floor_depth - 1 {
-            x -= 1;
-            y += 1;
-        } else if !grid.contains(&(x + 1, y + 1)) && y < floor_depth - 1 {
-            x += 1;
-            y += 1;
-        } else {
-            return Some((x, y));
-        }
+    let mut sim = SandSim::new(input.rocks, sources);
+    if let Some(offset) = floor {
+        sim = sim.with_floor(offset);
     }
-}
-
-fn _draw_grid(rocks: &HashSet<Point>, sand: &HashSet<Point>) {
-    for y in 0..175 {
-        print!("{} ", y);
-        for x in 480..520 {
-            let c = if sand.contains(&(x, y)) {
-                'o'
-            } else if rocks.contains(&(x, y)) {
-                '#'
-            } else if x == 500 {
-                '+'
-            } else {
-                '.'
-            };
-            print!("{}", c);
-        }
-        println!();
+    if let Some((left, right)) = walls {
+        sim = sim.with_walls(left, right);
     }
+    Ok(sim)
 }
 
-fn _debug() -> io::Result<u32> {
-    let mut input = Input::try_from(FileLines::new(INPUT_FILE)?)?;
-    let mut grains = 0;
-    let mut sand = HashSet::new();
-
-    _draw_grid(&input.grid, &sand);
-    println!();
-    while let Some(grain) = drop_grain_with_abyss(&input.grid, input.abyss_depth) {
-        sand.insert(grain);
-        input.grid.insert(grain);
-        grains += 1;
+pub fn tool(args: &[&str]) -> io::Result<()> {
+    match args {
+        [command @ ("run" | "render"), rest @ ..] => {
+            let sim = simulator(rest)?;
+            let outcome = sim.run();
+            if *command == "render" {
+                print!("{}", sim.render(&outcome.sand));
+            }
+            println!("Settled: {}", outcome.settled);
+            match outcome.overflow {
+                Some(Overflow::Abyss { grain, source }) => {
+                    println!("Grain {} from {:?} fell into the abyss", grain, source)
+                }
+                Some(Overflow::Blocked { grain, source }) => {
+                    println!("Grain {} found {:?} blocked", grain, source)
+                }
+                None => println!("No sources"),
+            }
+        }
+        _ => error("Usage: 14 (run|render) [source=x,y]... [floor=<offset>] [walls=<left>,<right>] [-- file]")?,
     }
-    _draw_grid(&input.grid, &sand);
-    Ok(grains)
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
+    use super::sand::*;
+    use super::{_part_1_sample, _part_2_sample, part_1, part_2, FileLines, Input, SOURCE};
+    use std::collections::HashSet;
+
+    fn sample() -> HashSet<Point> {
+        Input::try_from(FileLines::new(super::_INPUT_FILE_SAMPLE).unwrap())
+            .unwrap()
+            .rocks
+    }
+
+    #[test]
+    fn test_sand_sim() {
+        let outcome = SandSim::new(sample(), vec![SOURCE]).run();
+        assert_eq!(24, outcome.settled);
+        assert_eq!(24, outcome.sand.len());
+        let overflow = Overflow::Abyss {
+            grain: 25,
+            source: SOURCE,
+        };
+        assert_eq!(Some(overflow), outcome.overflow);
+
+        let sim = SandSim::new(sample(), vec![SOURCE]).with_floor(2);
+        let outcome = sim.run();
+        assert_eq!(93, outcome.settled);
+        let overflow = Overflow::Blocked {
+            grain: 94,
+            source: SOURCE,
+        };
+        assert_eq!(Some(overflow), outcome.overflow);
+        assert_eq!(12, sim.render(&outcome.sand).lines().count());
+
+        let shifted: HashSet<Point> = sample().iter().map(|(x, y)| (x - 600, y - 10)).collect();
+        let outcome = SandSim::new(shifted, vec![(-100, -10)]).run();
+        assert_eq!(24, outcome.settled);
+        assert!(outcome.sand.contains(&(-100, -8)));
+
+        let outcome = SandSim::new(sample(), vec![]).run();
+        assert_eq!((0, None), (outcome.settled, outcome.overflow));
+    }
+
+    #[test]
+    fn test_walls_and_sources() {
+        let sim = SandSim::new(sample(), vec![SOURCE])
+            .with_floor(2)
+            .with_walls(495, 505);
+        let outcome = sim.run();
+        assert_eq!(52, outcome.settled);
+        assert!(outcome.sand.iter().all(|(x, _)| (496..505).contains(x)));
+
+        let sources = vec![(490, 0), (510, 0)];
+        let outcome = SandSim::new(sample(), sources).with_floor(5).run();
+        assert_eq!(326, outcome.settled);
+        assert!(matches!(outcome.overflow, Some(Overflow::Blocked { .. })));
+    }
 
     #[test]
     fn test_part_1() {
@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+
+pub type Point = (i64, i64);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    Abyss { grain: usize, source: Point },
+    Blocked { grain: usize, source: Point },
+}
+
+#[derive(Debug)]
+pub struct Outcome {
+    pub settled: usize,
+    pub overflow: Option<Overflow>,
+    pub sand: HashSet<Point>,
+}
+
+#[derive(Debug)]
+pub struct SandSim {
+    pub rocks: HashSet<Point>,
+    pub sources: Vec<Point>,
+    pub floor: Option<i64>,
+    pub walls: Option<(i64, i64)>,
+}
+
+impl SandSim {
+    pub fn new(rocks: HashSet<Point>, sources: Vec<Point>) -> Self {
+        SandSim {
+            rocks,
+            sources,
+            floor: None,
+            walls: None,
+        }
+    }
+
+    pub fn with_floor(mut self, offset: i64) -> Self {
+        self.floor = Some(self.depth() + offset);
+        self
+    }
+
+    pub fn with_walls(mut self, left: i64, right: i64) -> Self {
+        self.walls = Some((left, right));
+        self
+    }
+
+    fn depth(&self) -> i64 {
+        self.rocks
+            .iter()
+            .chain(&self.sources)
+            .map(|p| p.1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn blocked(&self, sand: &HashSet<Point>, point: Point) -> bool {
+        self.rocks.contains(&point)
+            || sand.contains(&point)
+            || self.floor.is_some_and(|floor| point.1 >= floor)
+            || self
+                .walls
+                .is_some_and(|(left, right)| point.0 <= left || point.0 >= right)
+    }
+
+    fn fall(&self, sand: &HashSet<Point>, path: &mut Vec<Point>, abyss: i64) -> Option<Point> {
+        while path.last().is_some_and(|p| self.blocked(sand, *p)) {
+            path.pop();
+        }
+        let (mut x, mut y) = *path.last()?;
+        while y < abyss {
+            match [(x, y + 1), (x - 1, y + 1), (x + 1, y + 1)]
+                .into_iter()
+                .find(|p| !self.blocked(sand, *p))
+            {
+                Some(next) => {
+                    (x, y) = next;
+                    path.push(next);
+                }
+                None => return Some((x, y)),
+            }
+        }
+        None
+    }
+
+    pub fn run(&self) -> Outcome {
+        let abyss = self.floor.unwrap_or_else(|| self.depth());
+        let mut sand = HashSet::new();
+        let mut settled = 0;
+        if self.sources.is_empty() {
+            return Outcome {
+                settled,
+                overflow: None,
+                sand,
+            };
+        }
+        let mut paths: Vec<Vec<Point>> = self.sources.iter().map(|s| vec![*s]).collect();
+        let overflow = loop {
+            let i = settled % self.sources.len();
+            let (source, grain) = (self.sources[i], settled + 1);
+            if self.blocked(&sand, source) {
+                break Overflow::Blocked { grain, source };
+            }
+            match self.fall(&sand, &mut paths[i], abyss) {
+                Some(point) => {
+                    sand.insert(point);
+                    settled += 1;
+                }
+                None => break Overflow::Abyss { grain, source },
+            }
+        };
+        Outcome {
+            settled,
+            overflow: Some(overflow),
+            sand,
+        }
+    }
+
+    pub fn render(&self, sand: &HashSet<Point>) -> String {
+        let points: Vec<&Point> = self.rocks.iter().chain(sand).chain(&self.sources).collect();
+        let left = points.iter().map(|p| p.0).min().unwrap_or(0) - 1;
+        let right = points.iter().map(|p| p.0).max().unwrap_or(0) + 1;
+        let top = points.iter().map(|p| p.1).min().unwrap_or(0);
+        let bottom = self.floor.unwrap_or_else(|| self.depth());
+        let mut output = String::new();
+        for y in top..=bottom {
+            for x in left..=right {
+                output.push(if sand.contains(&(x, y)) {
+                    'o'
+                } else if self.sources.contains(&(x, y)) {
+                    '+'
+                } else if self.blocked(sand, (x, y)) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
@@ -1,3 +1,4 @@
+use crate::interval::IntervalSet;
 use crate::parser::*;
 use std::cmp::{max, min};
 use std::collections::HashSet;
@@ -7,7 +8,6 @@ const INPUT_FILE: &str = "input/input15.txt";
 const _INPUT_FILE_SAMPLE: &str = "input/input15_sample.txt";
 
 type Range = (i32, i32);
-type Ranges = Vec<(i32, i32)>;
 
 #[derive(Debug)]
 struct Sensor {
@@ -79,7 +79,7 @@ pub fn part_1() -> io::Result<usize> {
     do_part_1(INPUT_FILE, ROW)
 }
 
-fn _part_1_sample() -> io::Result<usize> {
+pub fn _part_1_sample() -> io::Result<usize> {
     const ROW: i32 = 10;
     do_part_1(_INPUT_FILE_SAMPLE, ROW)
 }
@@ -110,7 +110,7 @@ pub fn part_2() -> io::Result<u64> {
     do_part_2(INPUT_FILE, LIMIT)
 }
 
-fn _part_2_sample() -> io::Result<u64> {
+pub fn _part_2_sample() -> io::Result<u64> {
     const LIMIT: usize = 20;
     do_part_2(_INPUT_FILE_SAMPLE, LIMIT)
 }
@@ -126,25 +126,25 @@ fn do_part_2(input_file: &str, limit: usize) -> io::Result<u64> {
 }
 
 fn find_uncovered(sensors: &Vec<Sensor>, row: usize, range: Range) -> Option<i32> {
-    let mut covered: Ranges = Vec::new();
+    let mut covered = IntervalSet::new();
+    let full = (i64::from(range.0), i64::from(range.1));
     for sensor in sensors.iter() {
-        match sensor.projection_range(row as i32, range.1) {
-            Some(range) => covered = merge_ranges(covered, range),
-            None => {}
+        if let Some((a, b)) = sensor.projection_range(row as i32, range.1) {
+            covered.insert((i64::from(a), i64::from(b)));
         }
         let (bx, by) = sensor.beacon;
         if by as usize == row && bx >= range.0 && bx <= range.1 {
-            covered = merge_ranges(covered, (sensor.beacon.0, sensor.beacon.0));
+            covered.insert((i64::from(bx), i64::from(bx)));
         }
-        if covered.len() >= 1 && covered[0] == range {
+        if covered.intervals().first() == Some(&full) {
             println!("Row {} is fully covered!", row);
             return None;
         }
     }
-    println!("Sensor sweep finished! Covered = {:?}", covered);
-    if covered.len() != 1 || covered[0] != range {
-        match covered[0] {
-            (0, x) => Some(x + 1),
+    println!("Sensor sweep finished! Covered = {:?}", covered.intervals());
+    if covered.intervals() != [full] {
+        match covered.intervals()[0] {
+            (0, x) => Some(x as i32 + 1),
             _ => Some(0),
         }
     } else {
@@ -152,47 +152,6 @@ fn find_uncovered(sensors: &Vec<Sensor>, row: usize, range: Range) -> Option<i32
     }
 }
 
-fn merge_ranges(ranges: Ranges, range: Range) -> Ranges {
-    let length = ranges.len();
-    if length == 0 {
-        return vec![range];
-    }
-    let (l1, r1) = range;
-    let mut result = vec![];
-    let mut insert_left: usize = 0;
-    let mut insert_right: usize = 0;
-    for i in 0..length {
-        if l1 <= ranges[i].1 + 1 {
-            insert_left = i;
-            insert_right = i;
-            while insert_right + 1 < length && r1 + 1 >= ranges[insert_right + 1].0 {
-                insert_right += 1;
-            }
-            break;
-        } else {
-            insert_left = i + 1;
-            insert_right = i + 1;
-            result.push(ranges[i]);
-        }
-    }
-    match (insert_left, insert_right) {
-        (i, _) if i == length => result.push(range),
-        (i, _) if r1 + 1 < ranges[i].0 => {
-            result.push(range);
-            result.push(ranges[i]);
-        }
-        (i, j) => {
-            let a = min(l1, ranges[i].0);
-            let b = max(r1, ranges[j].1);
-            result.push((a, b));
-        }
-    }
-    for i in insert_right + 1..length {
-        result.push(ranges[i]);
-    }
-    result
-}
-
 fn tuning_frequency(beacon: Range) -> u64 {
     let (x, y) = beacon;
     x as u64 * 4_000_000 + y as u64
@@ -200,35 +159,7 @@ fn tuning_frequency(beacon: Range) -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use super::{_part_1_sample, _part_2_sample, merge_ranges, part_1, part_2};
-
-    #[test]
-    fn test_merge_ranges() {
-        // Add
-        assert_eq!(vec![(2, 3)], merge_ranges(vec![], (2, 3)));
-
-        // Span
-        assert_eq!(vec![(0, 5)], merge_ranges(vec![(0, 5)], (3, 4)));
-        assert_eq!(vec![(0, 5)], merge_ranges(vec![(3, 4)], (0, 5)));
-        assert_eq!(vec![(1, 4)], merge_ranges(vec![(1, 4)], (3, 3)));
-        assert_eq!(vec![(1, 4)], merge_ranges(vec![(3, 3)], (1, 4)));
-
-        // Disjoint
-        assert_eq!(vec![(0, 1), (3, 4)], merge_ranges(vec![(3, 4)], (0, 1)));
-        assert_eq!(vec![(0, 1), (3, 4)], merge_ranges(vec![(0, 1)], (3, 4)));
-
-        // Meet
-        assert_eq!(vec![(0, 3)], merge_ranges(vec![(2, 3)], (0, 1)));
-        assert_eq!(vec![(0, 3)], merge_ranges(vec![(0, 1)], (2, 3)));
-        assert_eq!(vec![(1, 3)], merge_ranges(vec![(2, 3)], (1, 1)));
-        assert_eq!(vec![(1, 3)], merge_ranges(vec![(1, 1)], (2, 3)));
-        assert_eq!(vec![(1, 3)], merge_ranges(vec![(3, 3)], (1, 2)));
-        assert_eq!(vec![(1, 3)], merge_ranges(vec![(1, 2)], (3, 3)));
-
-        // Straddle
-        assert_eq!(vec![(0, 20)], merge_ranges(vec![(0, 10)], (8, 20)));
-        assert_eq!(vec![(0, 20)], merge_ranges(vec![(8, 20)], (0, 10)));
-    }
+    use super::{_part_1_sample, _part_2_sample, part_1, part_2};
 
     #[test]
     fn test_part_1() {
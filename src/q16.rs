@@ -57,7 +57,7 @@ pub fn part_1() -> io::Result<usize> {
     Ok(do_part_1(input))
 }
 
-fn _part_1_sample() -> io::Result<usize> {
+pub fn _part_1_sample() -> io::Result<usize> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_1(input))
 }
@@ -81,7 +81,7 @@ pub fn part_2() -> io::Result<usize> {
     Ok(do_part_2(input))
 }
 
-fn _part_2_sample() -> io::Result<usize> {
+pub fn _part_2_sample() -> io::Result<usize> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_2(input))
 }
@@ -188,7 +188,7 @@ pub fn part_1() -> io::Result<u32> {
     Ok(chamber.max_height())
 }
 
-fn _part_1_sample() -> io::Result<u32> {
+pub fn _part_1_sample() -> io::Result<u32> {
     let chamber = simulate(2022, _INPUT_FILE_SAMPLE)?;
     Ok(chamber.max_height())
 }
@@ -199,7 +199,7 @@ pub fn part_2() -> io::Result<u64> {
     do_part_2(INPUT_FILE, shape_count, added_height)
 }
 
-fn _part_2_sample() -> io::Result<u64> {
+pub fn _part_2_sample() -> io::Result<u64> {
     let (shape_count, added_height) = calculate_fixed_point(_INPUT_FILE_SAMPLE)?;
 
     do_part_2(_INPUT_FILE_SAMPLE, shape_count, added_height)
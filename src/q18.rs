@@ -66,7 +66,7 @@ pub fn part_1() -> io::Result<i32> {
     do_part_1(INPUT_FILE)
 }
 
-fn _part_1_sample() -> io::Result<i32> {
+pub fn _part_1_sample() -> io::Result<i32> {
     do_part_1(_INPUT_FILE_SAMPLE)
 }
 
@@ -79,7 +79,7 @@ pub fn part_2() -> io::Result<i32> {
     do_part_2(INPUT_FILE)
 }
 
-fn _part_2_sample() -> io::Result<i32> {
+pub fn _part_2_sample() -> io::Result<i32> {
     do_part_2(_INPUT_FILE_SAMPLE)
 }
 
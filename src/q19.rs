@@ -56,7 +56,7 @@ pub fn part_1() -> io::Result<u32> {
     Ok(blueprints.map(|b| quality_level(&b, MINUTES)).sum())
 }
 
-fn _part_1_sample() -> io::Result<u32> {
+pub fn _part_1_sample() -> io::Result<u32> {
     const MINUTES: u32 = 24;
     let blueprints = parse_lines::<Blueprint>(_INPUT_FILE_SAMPLE)?;
     Ok(blueprints.map(|b| quality_level(&b, MINUTES)).sum())
@@ -71,7 +71,7 @@ pub fn part_2() -> io::Result<u64> {
         .product())
 }
 
-fn _part_2_sample() -> io::Result<u64> {
+pub fn _part_2_sample() -> io::Result<u64> {
     const MINUTES: u32 = 32;
     let blueprints = parse_lines::<Blueprint>(_INPUT_FILE_SAMPLE)?;
     Ok(blueprints
@@ -50,7 +50,7 @@ pub fn part_1() -> io::Result<i64> {
     Ok(do_part_1(&mut input))
 }
 
-fn _part_1_sample() -> io::Result<i64> {
+pub fn _part_1_sample() -> io::Result<i64> {
     let mut input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_1(&mut input))
 }
@@ -71,7 +71,7 @@ pub fn part_2() -> io::Result<i64> {
     Ok(do_part_2(&mut input))
 }
 
-fn _part_2_sample() -> io::Result<i64> {
+pub fn _part_2_sample() -> io::Result<i64> {
     let mut input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_2(&mut input))
 }
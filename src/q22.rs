@@ -363,7 +363,7 @@ pub fn part_1() -> io::Result<usize> {
     Ok(do_part_1(&mut input))
 }
 
-fn _part_1_sample() -> io::Result<usize> {
+pub fn _part_1_sample() -> io::Result<usize> {
     let mut input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_1(&mut input))
 }
@@ -381,7 +381,7 @@ pub fn part_2() -> io::Result<usize> {
     Ok(do_part_2(&mut input, Location::step_off_edge_real))
 }
 
-fn _part_2_sample() -> io::Result<usize> {
+pub fn _part_2_sample() -> io::Result<usize> {
     let mut input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_2(&mut input, Location::_step_off_edge_sample))
 }
@@ -158,7 +158,7 @@ pub fn part_1() -> io::Result<u32> {
     Ok(do_part_1(input))
 }
 
-fn _part_1_sample() -> io::Result<u32> {
+pub fn _part_1_sample() -> io::Result<u32> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_1(input))
 }
@@ -174,7 +174,7 @@ pub fn part_2() -> io::Result<u32> {
     Ok(do_part_2(input))
 }
 
-fn _part_2_sample() -> io::Result<u32> {
+pub fn _part_2_sample() -> io::Result<u32> {
     let input = Input::try_from(&mut FileLines::new(_INPUT_FILE_SAMPLE)?)?;
     Ok(do_part_2(input))
 }
@@ -81,7 +81,7 @@ pub fn part_1() -> io::Result<String> {
     do_part_1(INPUT_FILE)
 }
 
-fn _part_1_sample() -> io::Result<String> {
+pub fn _part_1_sample() -> io::Result<String> {
     do_part_1(_INPUT_FILE_SAMPLE)
 }
 
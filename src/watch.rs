@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+const INPUT_DIR: &str = "input";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub type Solver = fn() -> io::Result<String>;
+
+type Snapshot = HashMap<PathBuf, SystemTime>;
+
+pub fn watch(number: u8, solvers: &[(&str, Solver)]) -> io::Result<()> {
+    let mut snapshot = input_files(number)?;
+    let mut previous: HashMap<&str, String> = HashMap::new();
+    println!(
+        "Watching {} input files for question {}",
+        snapshot.len(),
+        number
+    );
+    run(number, solvers, &mut previous);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let current = match input_files(number) {
+            Ok(current) => current,
+            Err(e) => {
+                eprintln!("Error! {:}", e);
+                continue;
+            }
+        };
+        if current != snapshot {
+            snapshot = current;
+            run(number, solvers, &mut previous);
+        }
+    }
+}
+
+fn input_files(number: u8) -> io::Result<Snapshot> {
+    let prefix = format!("input{:02}", number);
+    let mut files = Snapshot::new();
+    for entry in fs::read_dir(INPUT_DIR)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            files.insert(entry.path(), entry.metadata()?.modified()?);
+        }
+    }
+    Ok(files)
+}
+
+fn run<'a>(number: u8, solvers: &[(&'a str, Solver)], previous: &mut HashMap<&'a str, String>) {
+    println!("------------");
+    println!("Question {:}", number);
+    for (label, solver) in solvers {
+        let start = Instant::now();
+        let answer = match panic::catch_unwind(solver) {
+            Ok(Ok(answer)) => answer,
+            Ok(Err(e)) => format!("Error! {:}", e),
+            Err(_) => String::from("Panicked!"),
+        };
+        let elapsed = start.elapsed();
+        println!("{:}: {:} [{:.2?}]", label, answer.trim(), elapsed);
+        if let Some(old) = previous.get(label) {
+            for line in diff(old, &answer) {
+                println!("    {:}", line);
+            }
+        }
+        previous.insert(label, answer);
+    }
+}
+
+fn diff(old: &str, new: &str) -> Vec<String> {
+    if old == new {
+        return vec![String::from("(unchanged)")];
+    }
+    let old_lines: Vec<&str> = old.trim().lines().collect();
+    let new_lines: Vec<&str> = new.trim().lines().collect();
+    let mut result = vec![];
+    for i in 0..old_lines.len().max(new_lines.len()) {
+        match (old_lines.get(i), new_lines.get(i)) {
+            (Some(a), Some(b)) if a == b => {}
+            (a, b) => {
+                if let Some(a) = a {
+                    result.push(format!("- {:}", a));
+                }
+                if let Some(b) = b {
+                    result.push(format!("+ {:}", b));
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(vec!["(unchanged)"], diff("24000", "24000"));
+        assert_eq!(vec!["- 24000", "+ 25000"], diff("24000", "25000"));
+        assert_eq!(
+            vec!["- ..#", "+ .##", "+ ###"],
+            diff("\n#..\n..#\n", "\n#..\n.##\n###\n")
+        );
+    }
+}